@@ -0,0 +1,58 @@
+use {
+    criterion::{black_box, criterion_group, criterion_main, Criterion},
+    ioc_rs::{Registered, ServiceCollection, ServiceProviderFactory},
+    std::sync::Arc,
+};
+
+#[derive(Clone)]
+struct HttpRequest(&'static str);
+
+fn create_factory() -> ServiceProviderFactory<HttpRequest> {
+    let mut collection = ServiceCollection::new();
+    collection
+        .with::<Registered<HttpRequest>>()
+        .register_shared(|request| Arc::new(request.0.len()));
+    collection
+        .with::<Registered<Arc<usize>>>()
+        .register_shared(|length| Arc::new(*length as u64));
+    // Shared services, which are never requested, still need storage in every ServiceProvider
+    collection.register_shared(|| Arc::new(1u8));
+    collection.register_shared(|| Arc::new(1u16));
+    collection.register_shared(|| Arc::new(1u32));
+    collection.register_shared(|| Arc::new(1i8));
+    collection.register_shared(|| Arc::new(1i16));
+    collection.register_shared(|| Arc::new(1i32));
+    collection.register_shared(|| Arc::new(1i64));
+    collection.build_factory().expect("Configuration is valid")
+}
+
+fn build_per_request(c: &mut Criterion) {
+    let factory = create_factory();
+    let mut group = c.benchmark_group("build per request");
+    group.bench_function("build", |b| {
+        b.iter(|| {
+            let provider = factory.build(HttpRequest("/index.html"));
+            black_box(provider.get::<Arc<u64>>().map(|l| *l))
+        })
+    });
+    group.bench_function("build_pooled", |b| {
+        b.iter(|| {
+            let provider = factory.build_pooled(HttpRequest("/index.html"));
+            black_box(provider.get::<Arc<u64>>().map(|l| *l))
+        })
+    });
+    group.finish();
+
+    // Only the storage of the ServiceProvider, which is what build_pooled() recycles
+    let mut group = c.benchmark_group("build without resolving");
+    group.bench_function("build", |b| {
+        b.iter(|| black_box(factory.build(HttpRequest("/index.html"))))
+    });
+    group.bench_function("build_pooled", |b| {
+        b.iter(|| black_box(factory.build_pooled(HttpRequest("/index.html"))))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, build_per_request);
+criterion_main!(benches);
@@ -0,0 +1,281 @@
+use {
+    crate::{
+        resolvable::{Dependency, DependencyKind},
+        ServiceCollection, ServiceLifetime, ServiceMetadata,
+    },
+    alloc::{collections::BTreeMap, vec::Vec},
+    core::{any::TypeId, panic::Location},
+};
+
+/// Potential misconfiguration found by `ServiceCollection::analyze()`, which doesn't prevent building a ServiceProvider
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// Service the diagnostic refers to
+    pub service: &'static str,
+    /// Call to `register*()`, which registered the service
+    pub registered_at: Option<&'static Location<'static>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Might be intended, e.g. services which are only resolved directly from the ServiceProvider
+    Info,
+    Warning,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// A later registration of the same type is resolved for `Registered<T>` and no service requests
+    /// `AllRegistered<T>`, so this registration is never used by other services
+    ShadowedRegistration,
+    /// A shared service keeps a `WeakServiceProvider`, which prevents the ServiceProvider from disposing
+    /// its shared services if the shared service is leaked
+    SharedServiceDependsOnProvider,
+    /// A shared service depends on a transient service, which is thus created only once per ServiceProvider
+    TransientCapturedByShared { dependency: &'static str },
+    /// No other service depends on this registration
+    UnusedRegistration,
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind {
+            DiagnosticKind::ShadowedRegistration => write!(
+                f,
+                "{} is shadowed by a later registration and never requested with AllRegistered",
+                self.service
+            ),
+            DiagnosticKind::SharedServiceDependsOnProvider => write!(
+                f,
+                "Shared service {} depends on WeakServiceProvider",
+                self.service
+            ),
+            DiagnosticKind::TransientCapturedByShared { dependency } => write!(
+                f,
+                "Shared service {} captures the transient service {}",
+                self.service, dependency
+            ),
+            DiagnosticKind::UnusedRegistration => {
+                write!(f, "No service depends on {}", self.service)
+            }
+        }?;
+        if let Some(location) = self.registered_at {
+            write!(f, " (registered at {})", location)?;
+        }
+        Ok(())
+    }
+}
+
+impl ServiceCollection {
+    /// Looks for registrations, which are valid but likely unintended. Unlike `build()`, nothing is instantiated
+    /// and the ServiceCollection can still be modified afterwards:
+    /// ```
+    /// use {ioc_rs::{DiagnosticKind, Registered, ServiceCollection, Severity}, std::sync::Arc};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.register(|| 1u8);
+    /// collection.register(|| 2u8);
+    /// collection.with::<Registered<u8>>().register_shared(|byte| Arc::new(byte as u16));
+    ///
+    /// let diagnostics = collection.analyze();
+    /// let kinds: Vec<_> = diagnostics.iter().map(|d| (d.severity, d.kind)).collect();
+    /// assert_eq!(
+    ///     vec![
+    ///         (Severity::Warning, DiagnosticKind::ShadowedRegistration),
+    ///         (Severity::Warning, DiagnosticKind::TransientCapturedByShared { dependency: "u8" }),
+    ///         (Severity::Info, DiagnosticKind::UnusedRegistration),
+    ///     ],
+    ///     kinds
+    /// );
+    /// ```
+    /// Diagnostics are ordered by the registration of their services
+    pub fn analyze(&self) -> Vec<Diagnostic> {
+        let services: Vec<(TypeId, &ServiceMetadata)> = self
+            .producer_factories
+            .iter()
+            .map(|p| (p.type_id, &p.metadata))
+            .collect();
+        let mut registrations: BTreeMap<TypeId, Vec<usize>> = BTreeMap::new();
+        for (i, (id, _)) in services.iter().enumerate() {
+            registrations.entry(*id).or_default().push(i);
+        }
+        let mut requested = BTreeMap::new();
+        for (_, metadata) in &services {
+            visit(metadata, |dependency| {
+                let all = requested.entry(dependency.type_id).or_insert(false);
+                *all |= dependency.kind == DependencyKind::AllRegistered;
+            });
+        }
+
+        let mut diagnostics = Vec::new();
+        for (i, (id, metadata)) in services.iter().enumerate() {
+            let mut report = |severity, kind| {
+                diagnostics.push(Diagnostic {
+                    severity,
+                    kind,
+                    service: metadata.type_name,
+                    registered_at: metadata.registered_at,
+                })
+            };
+            let is_shadowed = registrations[id].last() != Some(&i);
+            if is_shadowed && requested.get(id) != Some(&true) {
+                report(Severity::Warning, DiagnosticKind::ShadowedRegistration);
+            }
+            if metadata.lifetime == ServiceLifetime::Shared {
+                visit(metadata, |dependency| {
+                    let captured = match dependency.kind {
+                        DependencyKind::ServiceProvider => {
+                            report(
+                                Severity::Warning,
+                                DiagnosticKind::SharedServiceDependsOnProvider,
+                            );
+                            return;
+                        }
+                        DependencyKind::Registered => registrations
+                            .get(&dependency.type_id)
+                            .and_then(|positions| positions.last())
+                            .into_iter()
+                            .any(|pos| is_transient(services[*pos].1)),
+                        DependencyKind::AllRegistered => registrations
+                            .get(&dependency.type_id)
+                            .into_iter()
+                            .flatten()
+                            .any(|pos| is_transient(services[*pos].1)),
+                        // Services of parents aren't known before building
+                        DependencyKind::Inherited => false,
+                    };
+                    if captured {
+                        report(
+                            Severity::Warning,
+                            DiagnosticKind::TransientCapturedByShared {
+                                dependency: dependency.type_name,
+                            },
+                        );
+                    }
+                });
+            }
+            if !is_shadowed && !requested.contains_key(id) {
+                report(Severity::Info, DiagnosticKind::UnusedRegistration);
+            }
+        }
+        diagnostics
+    }
+}
+
+fn visit(metadata: &ServiceMetadata, mut visitor: impl FnMut(Dependency)) {
+    (metadata.declared_dependencies)(&mut visitor)
+}
+
+/// Aliases are transient conversions, which don't create a new service, so the lifetime of the aliased service counts
+fn is_transient(metadata: &ServiceMetadata) -> bool {
+    metadata.aliased.unwrap_or(metadata.lifetime) == ServiceLifetime::Transient
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{AllRegistered, Registered, WeakServiceProvider},
+        alloc::{format, sync::Arc},
+    };
+
+    #[test]
+    fn all_registered_makes_shadowed_registrations_reachable() {
+        let mut collection = ServiceCollection::new();
+        collection.register_instance(1u8);
+        collection.register_instance(2u8);
+        collection
+            .with::<AllRegistered<u8>>()
+            .register(|bytes| bytes.count());
+
+        let diagnostics = collection.analyze();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(DiagnosticKind::UnusedRegistration, diagnostics[0].kind);
+        assert_eq!("usize", diagnostics[0].service);
+    }
+
+    #[test]
+    fn aliases_have_the_lifetime_of_the_aliased_service() {
+        trait Db: Send + Sync {}
+        impl Db for u8 {}
+        impl Db for u16 {}
+
+        let mut collection = ServiceCollection::new();
+        collection
+            .register_shared(|| Arc::new(1u8))
+            .alias(|db| db as Arc<dyn Db>);
+        collection
+            .with::<Registered<Arc<dyn Db>>>()
+            .register_shared(|db| Arc::new((db, 1i32)));
+        assert!(collection
+            .analyze()
+            .iter()
+            .all(|d| !matches!(d.kind, DiagnosticKind::TransientCapturedByShared { .. })));
+
+        collection
+            .register(|| Arc::new(1u16))
+            .alias(|db| db as Arc<dyn Db>);
+        collection
+            .with::<Registered<Arc<dyn Db>>>()
+            .register_shared(|db| Arc::new((db, 2i32)));
+        let captured: Vec<_> = collection
+            .analyze()
+            .into_iter()
+            .filter(|d| matches!(d.kind, DiagnosticKind::TransientCapturedByShared { .. }))
+            .map(|d| d.service)
+            .collect();
+        assert_eq!(2, captured.len());
+    }
+
+    #[test]
+    fn aliases_of_aliases_have_the_lifetime_of_the_aliased_service() {
+        trait Db: Send + Sync {}
+        impl Db for u8 {}
+        struct Pool(#[allow(dead_code)] Arc<dyn Db>);
+
+        let mut collection = ServiceCollection::new();
+        collection
+            .register_shared(|| Arc::new(1u8))
+            .alias(|db| db as Arc<dyn Db>)
+            .alias(Pool);
+        collection
+            .with::<Registered<Pool>>()
+            .register_shared(|pool| Arc::new((pool, 1i32)));
+        assert!(collection
+            .analyze()
+            .iter()
+            .all(|d| !matches!(d.kind, DiagnosticKind::TransientCapturedByShared { .. })));
+    }
+
+    #[test]
+    fn shared_services_depending_on_provider_are_reported_with_location() {
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<WeakServiceProvider>()
+            .register_shared(Arc::new);
+        let line = line!() - 1;
+        collection
+            .with::<Registered<Arc<WeakServiceProvider>>>()
+            .register(|shared| shared.get::<i32>());
+
+        let diagnostics = collection.analyze();
+        let warning = &diagnostics[0];
+        assert_eq!(Severity::Warning, warning.severity);
+        assert_eq!(DiagnosticKind::SharedServiceDependsOnProvider, warning.kind);
+        assert_eq!(Some(line), warning.registered_at.map(|l| l.line()));
+        assert_eq!(
+            format!(
+                "Shared service alloc::sync::Arc<ioc_rs::WeakServiceProvider> depends on WeakServiceProvider (registered at {})",
+                warning.registered_at.unwrap()
+            ),
+            format!("{}", warning)
+        );
+        assert_eq!(
+            alloc::vec![DiagnosticKind::UnusedRegistration],
+            diagnostics[1..].iter().map(|d| d.kind).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use alloc::{collections::BTreeMap, string::String};
+
+/// Key-value settings loaded from all `ConfigurationSource`s of a `HostBuilder`.
+/// Nested settings use `:` as separator (e.g. `Logging:LogLevel`)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Configuration(BTreeMap<String, String>);
+
+impl Configuration {
+    /// Loads all sources in order. Values of later sources override values of earlier sources
+    pub fn load<'a>(sources: impl IntoIterator<Item = &'a dyn ConfigurationSource>) -> Self {
+        let mut values = BTreeMap::new();
+        for source in sources {
+            source.load(&mut values);
+        }
+        Self(values)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|v| v.as_str())
+    }
+
+    /// Parses the value of `key`. Returns None, if the key is missing or the value cannot be parsed
+    pub fn get_parsed<T: core::str::FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+/// Provides settings for a `Configuration`
+pub trait ConfigurationSource {
+    fn load(&self, values: &mut BTreeMap<String, String>);
+}
+
+impl<K: AsRef<str>, V: AsRef<str>, const N: usize> ConfigurationSource for [(K, V); N] {
+    fn load(&self, values: &mut BTreeMap<String, String>) {
+        for (key, value) in self {
+            values.insert(key.as_ref().into(), value.as_ref().into());
+        }
+    }
+}
+
+/// Loads all environment variables starting with `prefix`. The prefix is removed
+/// and `__` is replaced by `:`, so `APP_Logging__LogLevel` becomes `Logging:LogLevel` for the prefix `APP_`
+#[cfg(feature = "std")]
+pub struct EnvironmentVariables {
+    pub prefix: String,
+}
+
+#[cfg(feature = "std")]
+impl EnvironmentVariables {
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ConfigurationSource for EnvironmentVariables {
+    fn load(&self, values: &mut BTreeMap<String, String>) {
+        for (key, value) in std::env::vars() {
+            if let Some(key) = key.strip_prefix(self.prefix.as_str()) {
+                values.insert(key.replace("__", ":"), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_sources_override_earlier_sources() {
+        let defaults = [("Name", "default"), ("Port", "80")];
+        let overrides = [("Port", "8080")];
+        let configuration = Configuration::load([
+            &defaults as &dyn ConfigurationSource,
+            &overrides as &dyn ConfigurationSource,
+        ]);
+
+        assert_eq!(Some("default"), configuration.get("Name"));
+        assert_eq!(Some(8080u16), configuration.get_parsed("Port"));
+        assert_eq!(None, configuration.get_parsed::<u16>("Name"));
+    }
+
+    #[test]
+    fn load_prefixed_environment_variables() {
+        std::env::set_var("IOC_CONFIGURATION_TEST_Section__Key", "value");
+        let configuration =
+            Configuration::load([
+                &EnvironmentVariables::with_prefix("IOC_CONFIGURATION_TEST_")
+                    as &dyn ConfigurationSource,
+            ]);
+
+        assert_eq!(
+            vec![("Section:Key", "value")],
+            configuration.iter().collect::<Vec<_>>()
+        );
+    }
+}
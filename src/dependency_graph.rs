@@ -0,0 +1,373 @@
+use {
+    crate::{
+        resolvable::{Dependency, DependencyKind, ServicePosition, ServiceTypes},
+        ServiceLifetime, ServiceMetadata, ServiceProvider, WeakServiceProvider,
+    },
+    alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec},
+    core::{any::TypeId, panic::Location},
+};
+
+/// Services and their dependencies, which can be rendered with `to_dot()`:
+/// ```
+/// use {ioc_rs::{GraphNodeKind, Registered, ServiceCollection, ServiceLifetime}};
+///
+/// let mut collection = ServiceCollection::new();
+/// collection.with::<Registered<u8>>().register(|byte| byte as u16);
+/// collection.with::<Registered<u16>>().register(|short| short as u32);
+///
+/// let graph = collection.dependency_graph();
+/// assert_eq!(GraphNodeKind::Registered(ServiceLifetime::Transient), graph.nodes[0].kind);
+/// assert_eq!(GraphNodeKind::Missing, graph.nodes[2].kind);
+/// assert_eq!("u8", graph.nodes[2].type_name);
+/// assert!(graph.to_dot().starts_with("digraph dependencies {"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Registered services in registration order, followed by inherited and missing dependencies
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    pub type_name: &'static str,
+    pub kind: GraphNodeKind,
+    /// Call to `register*()`, which registered the service. None for missing dependencies and bases
+    pub registered_at: Option<&'static Location<'static>>,
+    /// The service depends on itself through its dependencies
+    pub in_cycle: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphNodeKind {
+    Registered(ServiceLifetime),
+    /// Registered in a parent ServiceProvider. Only services of parents which are dependencies are part of the graph
+    Inherited(ServiceLifetime),
+    /// A dependency, which isn't registered
+    Missing,
+}
+
+/// Points from a service to one of its dependencies
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphEdge {
+    /// Position of the dependent service in `DependencyGraph::nodes`
+    pub from: usize,
+    /// Position of the dependency in `DependencyGraph::nodes`
+    pub to: usize,
+    pub in_cycle: bool,
+}
+
+impl DependencyGraph {
+    /// `services` are the own services in registration order, whose dependencies are looked up in the same way as in
+    /// `ServiceCollection::validate_producers()`
+    pub(crate) fn new<'a>(
+        services: impl Iterator<Item = (TypeId, &'a ServiceMetadata)>,
+        parents: &[WeakServiceProvider],
+    ) -> Self {
+        let mut builder = GraphBuilder::default();
+        let mut own = Vec::new();
+        let mut dependents = Vec::new();
+        for (node, (id, metadata)) in services.enumerate() {
+            builder.nodes.push(GraphNode {
+                type_name: metadata.type_name,
+                kind: GraphNodeKind::Registered(metadata.lifetime),
+                registered_at: metadata.registered_at,
+                in_cycle: false,
+            });
+            own.push((id, node));
+            dependents.push(metadata.declared_dependencies);
+        }
+        own.sort_by_key(|(id, _)| *id);
+        let ids: Vec<_> = own.iter().map(|(id, _)| *id).collect();
+        builder.own = own.into_iter().map(|(_, node)| node).collect();
+        let types = ServiceTypes { ids: &ids, parents };
+
+        for (from, visit_dependencies) in dependents.into_iter().enumerate() {
+            visit_dependencies(&mut |dependency| builder.add_dependency(from, &types, dependency));
+        }
+        builder.mark_cycles();
+        DependencyGraph {
+            nodes: builder.nodes,
+            edges: builder.edges,
+        }
+    }
+
+    /// Renders the graph in the Graphviz DOT format. The shape of a node depends on its lifetime, inherited services
+    /// are dashed, missing dependencies orange and services which are part of a cycle red.
+    /// Registered services are labeled with the location of their registration
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let (description, shape) = match node.kind {
+                GraphNodeKind::Registered(lifetime) | GraphNodeKind::Inherited(lifetime) => {
+                    describe_lifetime(lifetime)
+                }
+                GraphNodeKind::Missing => ("missing", "octagon"),
+            };
+            let mut label = format!("{}\\n({})", escape(node.type_name), description);
+            if let Some(location) = node.registered_at {
+                label.push_str(&format!("\\n{}", escape(&format!("{}", location))));
+            }
+            let mut attributes = format!("label=\"{}\", shape={}", label, shape);
+            match node.kind {
+                GraphNodeKind::Inherited(_) => attributes.push_str(", style=dashed"),
+                GraphNodeKind::Missing => attributes.push_str(", color=orange, fontcolor=orange"),
+                GraphNodeKind::Registered(_) => {}
+            }
+            if node.in_cycle {
+                attributes.push_str(", color=red, fontcolor=red");
+            }
+            dot.push_str(&format!("    n{} [{}];\n", i, attributes));
+        }
+        for edge in &self.edges {
+            let attributes = if edge.in_cycle { " [color=red]" } else { "" };
+            dot.push_str(&format!(
+                "    n{} -> n{}{};\n",
+                edge.from, edge.to, attributes
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    /// Nodes of the own services ordered like `ServiceTypes::ids`
+    own: Vec<usize>,
+    inherited: BTreeMap<(usize, usize), usize>,
+    missing: BTreeMap<TypeId, usize>,
+}
+
+impl GraphBuilder {
+    fn add_dependency(&mut self, from: usize, types: &ServiceTypes, dependency: Dependency) {
+        let position = match dependency.kind {
+            DependencyKind::Registered => types.last(dependency.type_id),
+            DependencyKind::Inherited => types.last_inherited(dependency.type_id),
+            DependencyKind::AllRegistered => {
+                for position in types.positions(dependency.type_id) {
+                    let to = self.node_at(position);
+                    self.add_edge(from, to);
+                }
+                return;
+            }
+            DependencyKind::ServiceProvider => return,
+        };
+        let to = match position {
+            Some(position) => self.node_at(position),
+            None => self.missing_node(dependency),
+        };
+        self.add_edge(from, to);
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize) {
+        self.edges.push(GraphEdge {
+            from,
+            to,
+            in_cycle: false,
+        });
+    }
+
+    fn node_at(&mut self, position: ServicePosition) -> usize {
+        match position {
+            ServicePosition::Own(pos) => self.own[pos],
+            ServicePosition::Inherited(ancestor, pos) => {
+                let nodes = &mut self.nodes;
+                *self
+                    .inherited
+                    .entry((ancestor as usize, pos))
+                    .or_insert_with(|| {
+                        // Positions are only returned for parents passed to DependencyGraph::new(), which are still borrowed
+                        let ancestor: &ServiceProvider = unsafe { &*ancestor };
+                        let metadata = &ancestor.immutable_state.metadata[pos];
+                        nodes.push(GraphNode {
+                            type_name: metadata.type_name,
+                            kind: GraphNodeKind::Inherited(metadata.lifetime),
+                            registered_at: metadata.registered_at,
+                            in_cycle: false,
+                        });
+                        nodes.len() - 1
+                    })
+            }
+        }
+    }
+
+    fn missing_node(&mut self, dependency: Dependency) -> usize {
+        let nodes = &mut self.nodes;
+        *self.missing.entry(dependency.type_id).or_insert_with(|| {
+            nodes.push(GraphNode {
+                type_name: dependency.type_name,
+                kind: GraphNodeKind::Missing,
+                registered_at: None,
+                in_cycle: false,
+            });
+            nodes.len() - 1
+        })
+    }
+
+    /// An edge is part of a cycle, if its dependency depends on the dependent service
+    fn mark_cycles(&mut self) {
+        let mut dependencies = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            dependencies[edge.from].push(edge.to);
+        }
+        let mut reachable_from = BTreeMap::new();
+        for edge in &mut self.edges {
+            let reachable = reachable_from
+                .entry(edge.to)
+                .or_insert_with(|| reachable_nodes(&dependencies, edge.to));
+            if reachable[edge.from] {
+                edge.in_cycle = true;
+                self.nodes[edge.from].in_cycle = true;
+                self.nodes[edge.to].in_cycle = true;
+            }
+        }
+    }
+}
+
+fn reachable_nodes(dependencies: &[Vec<usize>], start: usize) -> Vec<bool> {
+    let mut reachable = vec![false; dependencies.len()];
+    let mut stack = vec![start];
+    reachable[start] = true;
+    while let Some(node) = stack.pop() {
+        for next in &dependencies[node] {
+            if !reachable[*next] {
+                reachable[*next] = true;
+                stack.push(*next);
+            }
+        }
+    }
+    reachable
+}
+
+fn describe_lifetime(lifetime: ServiceLifetime) -> (&'static str, &'static str) {
+    match lifetime {
+        ServiceLifetime::Transient => ("transient", "ellipse"),
+        ServiceLifetime::Shared => ("shared", "box"),
+        ServiceLifetime::Instance => ("instance", "note"),
+        ServiceLifetime::Base => ("base", "house"),
+    }
+}
+
+fn escape(type_name: &str) -> String {
+    type_name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{AllRegistered, Inherited, Registered, ServiceCollection},
+        alloc::sync::Arc,
+    };
+
+    #[test]
+    fn cycles_are_highlighted() {
+        let mut collection = ServiceCollection::new();
+        collection.with::<Registered<u16>>().register(|s| s as u8);
+        collection.with::<Registered<u8>>().register(|b| b as u16);
+        collection.with::<Registered<u8>>().register(|b| b as u32);
+
+        let graph = collection.dependency_graph();
+        assert_eq!(
+            alloc::vec![true, true, false],
+            graph.nodes.iter().map(|n| n.in_cycle).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            alloc::vec![(0, 1, true), (1, 0, true), (2, 0, false)],
+            graph
+                .edges
+                .iter()
+                .map(|e| (e.from, e.to, e.in_cycle))
+                .collect::<Vec<_>>()
+        );
+        assert!(graph.to_dot().contains("n0 -> n1 [color=red];"));
+        assert!(graph.to_dot().contains("n2 -> n0;"));
+    }
+
+    #[test]
+    fn nodes_are_labeled_with_their_registration() {
+        let mut collection = ServiceCollection::new();
+        collection.with::<Registered<u8>>().register(|b| b as u16);
+        let line = line!() - 1;
+
+        let graph = collection.dependency_graph();
+        let location = graph.nodes[0].registered_at.unwrap();
+        assert_eq!((file!(), line), (location.file(), location.line()));
+        assert_eq!(None, graph.nodes[1].registered_at);
+        assert!(graph
+            .to_dot()
+            .contains(&format!("label=\"u16\\n(transient)\\n{}\"", location)));
+    }
+
+    #[test]
+    fn dependencies_of_parents_are_inherited_nodes() {
+        let mut parent = ServiceCollection::new();
+        parent.register_shared(|| Arc::new(1i32));
+        parent.register(|| 1u8);
+        let parent = parent.build().unwrap();
+
+        let mut child = ServiceCollection::new();
+        child.register(|| 2u8);
+        child
+            .with::<(Inherited<Arc<i32>>, AllRegistered<u8>)>()
+            .register(|(i, bytes)| *i as u64 + bytes.map(|b| b as u64).sum::<u64>());
+        let builder = child.with_parent(&parent);
+
+        let graph = builder.dependency_graph();
+        assert_eq!(
+            alloc::vec![
+                GraphNodeKind::Registered(ServiceLifetime::Transient),
+                GraphNodeKind::Registered(ServiceLifetime::Transient),
+                GraphNodeKind::Inherited(ServiceLifetime::Shared),
+                GraphNodeKind::Inherited(ServiceLifetime::Transient),
+            ],
+            graph.nodes.iter().map(|n| n.kind).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            alloc::vec![(1, 2), (1, 3), (1, 0)],
+            graph
+                .edges
+                .iter()
+                .map(|e| (e.from, e.to))
+                .collect::<Vec<_>>()
+        );
+        assert!(graph.to_dot().contains("style=dashed"));
+    }
+
+    #[test]
+    fn base_services_are_part_of_built_providers() {
+        let mut collection = ServiceCollection::new();
+        collection.with::<Registered<i32>>().register(|i| i as i64);
+        let provider = collection.build_factory::<i32>().unwrap().build(1);
+
+        let graph = provider.dependency_graph();
+        assert_eq!(2, graph.nodes.len());
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|n| n.kind == GraphNodeKind::Registered(ServiceLifetime::Base)));
+        assert_eq!(1, graph.edges.len());
+    }
+
+    #[test]
+    fn nodes_of_built_providers_are_in_registration_order() {
+        let mut collection = ServiceCollection::new();
+        collection.register(|| 1u64);
+        collection.register(|| 1i8);
+        collection.with::<Registered<u64>>().register(|l| l as u16);
+        collection.register(|| 1u32);
+        collection.register(|| 2i8);
+        let expected = collection.dependency_graph();
+        let provider = collection.build_factory::<i32>().unwrap().build(1);
+
+        let graph = provider.dependency_graph();
+        assert_eq!(
+            alloc::vec!["u64", "i8", "u16", "u32", "i8", "i32"],
+            graph.nodes.iter().map(|n| n.type_name).collect::<Vec<_>>()
+        );
+        assert_eq!(expected.edges, graph.edges);
+    }
+}
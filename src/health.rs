@@ -0,0 +1,312 @@
+use {
+    crate::{initialization::panic_message, AllRegistered, Registered, ServiceCollection},
+    alloc::{string::String, sync::Arc, vec::Vec},
+    core::any::{Any, TypeId},
+    std::{
+        sync::mpsc,
+        time::{Duration, Instant},
+    },
+};
+
+/// Ordered from best to worst, so the status of a HealthReport is the maximum of all checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthCheckResult {
+    pub status: HealthStatus,
+    pub description: Option<String>,
+}
+
+impl HealthCheckResult {
+    pub fn healthy() -> Self {
+        Self {
+            status: HealthStatus::Healthy,
+            description: None,
+        }
+    }
+
+    pub fn degraded(description: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Degraded,
+            description: Some(description.into()),
+        }
+    }
+
+    pub fn unhealthy(description: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Unhealthy,
+            description: Some(description.into()),
+        }
+    }
+}
+
+/// Reports the health of a service. Register it with `ServiceCollection::add_health_check()`
+pub trait HealthCheck: Send + Sync {
+    fn check(&self) -> HealthCheckResult;
+
+    /// Identifies the check in a HealthReport
+    fn name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+/// Can be registered as instance to configure how `HealthReport`s are created.
+/// If it's registered multiple times, the last registration is used
+#[derive(Clone, Debug)]
+pub struct HealthCheckOptions {
+    /// Checks which don't finish in time are reported as Unhealthy. They keep running in the background,
+    /// so they must finish before the ServiceProvider is dropped to prevent leaking shared services
+    pub timeout: Duration,
+}
+
+impl Default for HealthCheckOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthReportEntry {
+    pub name: &'static str,
+    pub status: HealthStatus,
+    pub description: Option<String>,
+    pub duration: Duration,
+}
+
+/// Result of all registered HealthChecks, which are executed in parallel whenever a `HealthReport` is resolved
+/// ```
+/// use {ioc_rs::{HealthCheck, HealthCheckResult, HealthReport, HealthStatus, ServiceCollection}, std::sync::Arc};
+///
+/// struct Database;
+/// impl HealthCheck for Database {
+///     fn check(&self) -> HealthCheckResult {
+///         HealthCheckResult::degraded("Replica is unavailable")
+///     }
+/// }
+///
+/// let mut collection = ServiceCollection::new();
+/// collection.register_shared(|| Arc::new(Database));
+/// collection.add_health_check::<Database>();
+///
+/// let provider = collection.build().expect("Configuration is valid");
+/// let report = provider.get::<HealthReport>().expect("Registered by add_health_check()");
+/// assert_eq!(HealthStatus::Degraded, report.status);
+/// assert_eq!(Some("Replica is unavailable"), report.entries[0].description.as_deref());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Worst status of all entries, Healthy if no check is registered
+    pub status: HealthStatus,
+    /// Results in registration order
+    pub entries: Vec<HealthReportEntry>,
+    pub duration: Duration,
+}
+
+impl HealthReport {
+    /// Runs all checks in parallel and waits for each of them until `options.timeout` elapsed
+    pub fn run(
+        checks: impl IntoIterator<Item = Arc<dyn HealthCheck>>,
+        options: &HealthCheckOptions,
+    ) -> Self {
+        let start = Instant::now();
+        let pending: Vec<_> = checks
+            .into_iter()
+            .map(|check| {
+                let name = check.name();
+                let (sender, receiver) = mpsc::channel();
+                std::thread::spawn(move || {
+                    let check_start = Instant::now();
+                    // The check is moved into the closure, so it's dropped before the result is sent.
+                    // Otherwise, the ServiceProvider might be dropped while a shared check is still referenced
+                    let result =
+                        std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+                            check.check()
+                        }))
+                        .unwrap_or_else(|e| HealthCheckResult::unhealthy(panic_message(&*e)));
+                    // The receiver is gone if the check timed out
+                    let _ = sender.send((result, check_start.elapsed()));
+                });
+                (name, receiver)
+            })
+            .collect();
+
+        let deadline = start + options.timeout;
+        let entries: Vec<_> = pending
+            .into_iter()
+            .map(|(name, receiver)| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let (result, duration) = receiver.recv_timeout(remaining).unwrap_or_else(|_| {
+                    (
+                        HealthCheckResult::unhealthy(alloc::format!(
+                            "Timed out after {:?}",
+                            options.timeout
+                        )),
+                        options.timeout,
+                    )
+                });
+                HealthReportEntry {
+                    name,
+                    status: result.status,
+                    description: result.description,
+                    duration,
+                }
+            })
+            .collect();
+
+        HealthReport {
+            status: entries
+                .iter()
+                .map(|e| e.status)
+                .max()
+                .unwrap_or(HealthStatus::Healthy),
+            entries,
+            duration: start.elapsed(),
+        }
+    }
+}
+
+impl ServiceCollection {
+    /// Registers the shared service `Arc<T>` as `Arc<dyn HealthCheck>`, which is executed whenever a
+    /// transient `HealthReport` is resolved. `Arc<T>` has to be registered separately, otherwise building
+    /// fails with `BuildError::MissingDependency`.
+    ///
+    /// A check exceeding `HealthCheckOptions::timeout` keeps running in a detached thread, which holds a reference
+    /// to `Arc<T>` until the check returns. If the ServiceProvider is dropped or closed in the meantime,
+    /// `Arc<T>` is reported as leaked according to its `LeakPolicy`
+    #[track_caller]
+    pub fn add_health_check<T: HealthCheck + Any>(&mut self) {
+        self.with::<Registered<Arc<T>>>()
+            .register(|check| check as Arc<dyn HealthCheck>);
+
+        let report_type = TypeId::of::<Registered<HealthReport>>();
+        if !self
+            .producer_factories
+            .iter()
+            .any(|p| p.type_id == report_type)
+        {
+            self.with::<(
+                AllRegistered<Arc<dyn HealthCheck>>,
+                AllRegistered<HealthCheckOptions>,
+            )>()
+            .register(|(checks, options)| {
+                HealthReport::run(checks, &options.last().unwrap_or_default())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{BuildError, ServiceCollection},
+    };
+
+    struct Healthy;
+    impl HealthCheck for Healthy {
+        fn check(&self) -> HealthCheckResult {
+            HealthCheckResult::healthy()
+        }
+    }
+
+    struct Panicking;
+    impl HealthCheck for Panicking {
+        fn check(&self) -> HealthCheckResult {
+            panic!("Connection refused")
+        }
+        fn name(&self) -> &'static str {
+            "panicking"
+        }
+    }
+
+    struct Slow;
+    impl HealthCheck for Slow {
+        fn check(&self) -> HealthCheckResult {
+            std::thread::sleep(Duration::from_millis(100));
+            HealthCheckResult::healthy()
+        }
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+    }
+
+    #[test]
+    fn report_contains_all_checks_in_registration_order() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(Panicking));
+        collection.register_shared(|| Arc::new(Healthy));
+        collection.add_health_check::<Panicking>();
+        collection.add_health_check::<Healthy>();
+        let provider = collection.build().unwrap();
+
+        assert_eq!(1, provider.count::<HealthReport>());
+        let report = provider.get::<HealthReport>().unwrap();
+        assert_eq!(HealthStatus::Unhealthy, report.status);
+        assert_eq!(
+            vec![
+                (
+                    "panicking",
+                    HealthStatus::Unhealthy,
+                    Some("Connection refused")
+                ),
+                (
+                    core::any::type_name::<Healthy>(),
+                    HealthStatus::Healthy,
+                    None
+                ),
+            ],
+            report
+                .entries
+                .iter()
+                .map(|e| (e.name, e.status, e.description.as_deref()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn report_checks_exceeding_timeout_as_unhealthy() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(Healthy));
+        collection.add_health_check::<Healthy>();
+        // Transient checks aren't leak-checked, so they may outlive the provider after a timeout
+        collection.register(|| Arc::new(Slow) as Arc<dyn HealthCheck>);
+        collection.register_instance(HealthCheckOptions::default());
+        collection.register_instance(HealthCheckOptions {
+            timeout: Duration::from_millis(20),
+        });
+        let provider = collection.build().unwrap();
+
+        let report = provider.get::<HealthReport>().unwrap();
+        assert_eq!(HealthStatus::Unhealthy, report.status);
+        let slow = &report.entries[1];
+        assert_eq!(
+            ("slow", Some("Timed out after 20ms")),
+            (slow.name, slow.description.as_deref())
+        );
+        assert!(report.duration < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn healthy_without_checks() {
+        let report = HealthReport::run(Vec::new(), &HealthCheckOptions::default());
+        assert_eq!(HealthStatus::Healthy, report.status);
+        assert!(report.entries.is_empty());
+    }
+
+    #[test]
+    fn fail_if_health_check_is_not_registered() {
+        let mut collection = ServiceCollection::new();
+        collection.add_health_check::<Healthy>();
+        assert!(matches!(
+            collection.build(),
+            Err(BuildError::MissingDependency(_))
+        ));
+    }
+}
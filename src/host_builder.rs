@@ -0,0 +1,314 @@
+#[cfg(feature = "std")]
+use crate::LogLevel;
+use {
+    crate::{
+        BuildError, Configuration, ConfigurationSource, Host, HostedService, Log, ServiceCollection,
+    },
+    alloc::{boxed::Box, sync::Arc, vec::Vec},
+    core::any::Any,
+};
+
+/// Registered as instance by `HostBuilder`, so services can behave differently during development
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl Environment {
+    /// Reads the `IOC_ENVIRONMENT` variable. Everything except `Development` is treated as `Production`
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Self {
+        match std::env::var("IOC_ENVIRONMENT").as_deref() {
+            Ok("Development") => Environment::Development,
+            _ => Environment::Production,
+        }
+    }
+}
+
+/// Passed to the callbacks of a HostBuilder. `Environment` and `Arc<Configuration>` are registered as instances as well
+pub struct HostContext {
+    pub environment: Environment,
+    pub configuration: Arc<Configuration>,
+}
+
+type ConfigureServices = Box<dyn FnOnce(&mut ServiceCollection, &HostContext)>;
+type ConfigureLogging = fn(&HostContext) -> Arc<dyn Log>;
+
+/// Starts every application the same way: Configuration is loaded from all sources, `Environment`,
+/// `Arc<Configuration>` and a logger (`Arc<dyn Log>`) are registered, services are configured and
+/// `run()` starts all HostedServices until SIGINT or SIGTERM is received
+/// ```
+/// use {ioc_rs::{Configuration, Environment, HostBuilder, Registered}, std::sync::Arc};
+///
+/// let host = HostBuilder::new()
+///     .environment(Environment::Development)
+///     .add_configuration([("Greeting", "Hello")])
+///     .configure_services(|collection, _context| {
+///         collection
+///             .with::<Registered<Arc<Configuration>>>()
+///             .register(|c| format!("{} world", c.get("Greeting").unwrap_or("Hi")));
+///     })
+///     .build()
+///     .expect("Configuration is valid");
+///
+/// assert_eq!(Some("Hello world".to_string()), host.provider().get::<String>());
+/// ```
+pub struct HostBuilder {
+    collection: ServiceCollection,
+    environment: Option<Environment>,
+    sources: Vec<Box<dyn ConfigurationSource>>,
+    configure_services: Vec<ConfigureServices>,
+    logging: Option<ConfigureLogging>,
+}
+
+impl ServiceCollection {
+    /// Uses the already registered services as base of a HostBuilder
+    pub fn into_host_builder(self) -> HostBuilder {
+        HostBuilder {
+            collection: self,
+            environment: None,
+            sources: Vec::new(),
+            configure_services: Vec::new(),
+            logging: None,
+        }
+    }
+}
+
+impl Default for HostBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HostBuilder {
+    pub fn new() -> Self {
+        ServiceCollection::new().into_host_builder()
+    }
+
+    /// Defaults to `Environment::from_env()` with the `std` feature and to `Environment::Production` otherwise
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Values of sources added later override values of sources added earlier
+    pub fn add_configuration(mut self, source: impl ConfigurationSource + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    /// Replaces the default logger. With the `std` feature, the default is a `ConsoleLog` with the level
+    /// of `Logging:LogLevel` (`Debug` in Development and `Info` in Production if it's missing). Without `std`,
+    /// all messages are discarded by default
+    pub fn configure_logging(mut self, logging: ConfigureLogging) -> Self {
+        self.logging = Some(logging);
+        self
+    }
+
+    /// Callbacks are executed in the order they are added, after the configuration is loaded
+    pub fn configure_services(
+        mut self,
+        configure: impl FnOnce(&mut ServiceCollection, &HostContext) + 'static,
+    ) -> Self {
+        self.configure_services.push(Box::new(configure));
+        self
+    }
+
+    /// Same as `ServiceCollection::add_hosted()`
+    #[track_caller]
+    pub fn add_hosted<T: HostedService + Any>(mut self) -> Self {
+        self.collection.add_hosted::<T>();
+        self
+    }
+
+    /// Loads the configuration, configures all services and builds the Host without starting it
+    pub fn build(self) -> Result<Host, BuildError> {
+        let context = HostContext {
+            environment: self.environment.unwrap_or_else(default_environment),
+            configuration: Arc::new(Configuration::load(
+                self.sources.iter().map(|source| source.as_ref()),
+            )),
+        };
+        let mut collection = self.collection;
+        collection.register_instance(context.environment);
+        collection.register_instance(context.configuration.clone());
+        collection.register_instance(self.logging.unwrap_or(default_logging)(&context));
+        for configure in self.configure_services {
+            configure(&mut collection, &context);
+        }
+        Host::new(collection)
+    }
+
+    /// Builds the Host and runs all HostedServices until SIGINT or SIGTERM is received
+    #[cfg(feature = "std")]
+    pub fn run(self) -> Result<(), HostError> {
+        let host = self.build()?;
+        let log = host
+            .provider()
+            .get::<Arc<dyn Log>>()
+            .expect("Logger is registered by build()");
+        let environment = host
+            .provider()
+            .get::<Environment>()
+            .expect("Environment is registered by build()");
+        log.log(
+            LogLevel::Info,
+            &std::format!(
+                "Application started in {:?} environment. Press Ctrl+C to shut down",
+                environment
+            ),
+        );
+        host.run()?;
+        log.log(LogLevel::Info, "Application stopped");
+        Ok(())
+    }
+}
+
+/// Errors which can occur in `HostBuilder::run()`
+#[cfg(feature = "std")]
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum HostError {
+    Build(BuildError),
+    /// Waiting for SIGINT or SIGTERM failed
+    Signal(std::io::Error),
+}
+
+#[cfg(feature = "std")]
+impl From<BuildError> for HostError {
+    fn from(e: BuildError) -> Self {
+        HostError::Build(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for HostError {
+    fn from(e: std::io::Error) -> Self {
+        HostError::Signal(e)
+    }
+}
+
+#[cfg(feature = "std")]
+fn default_environment() -> Environment {
+    Environment::from_env()
+}
+
+#[cfg(not(feature = "std"))]
+fn default_environment() -> Environment {
+    Environment::Production
+}
+
+#[cfg(feature = "std")]
+fn default_logging(context: &HostContext) -> Arc<dyn Log> {
+    Arc::new(crate::ConsoleLog {
+        min_level: default_log_level(context),
+    })
+}
+
+#[cfg(feature = "std")]
+fn default_log_level(context: &HostContext) -> LogLevel {
+    context
+        .configuration
+        .get_parsed("Logging:LogLevel")
+        .unwrap_or(match context.environment {
+            Environment::Development => LogLevel::Debug,
+            Environment::Production => LogLevel::Info,
+        })
+}
+
+#[cfg(not(feature = "std"))]
+fn default_logging(_: &HostContext) -> Arc<dyn Log> {
+    Arc::new(crate::logging::NoLog)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::Registered, std::sync::Mutex};
+
+    static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct RecordingLog(&'static str);
+
+    impl Log for RecordingLog {
+        fn log(&self, level: LogLevel, message: &str) {
+            MESSAGES
+                .lock()
+                .unwrap()
+                .push(format!("{} {:?} {}", self.0, level, message));
+        }
+    }
+
+    struct Worker(Arc<dyn Log>);
+
+    impl HostedService for Worker {
+        fn start(&self) {
+            self.0.log(LogLevel::Info, "start");
+        }
+        fn stop(&self) {
+            self.0.log(LogLevel::Info, "stop");
+        }
+    }
+
+    #[test]
+    fn build_host_with_configuration_logging_and_hosted_services() {
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<Registered<Arc<dyn Log>>>()
+            .register_shared(|log| Arc::new(Worker(log)));
+
+        let host = collection
+            .into_host_builder()
+            .environment(Environment::Development)
+            .add_configuration([("Name", "default"), ("Level", "1")])
+            .add_configuration([("Name", "test")])
+            .configure_logging(|context| {
+                let name = context.configuration.get("Name").unwrap_or_default();
+                Arc::new(RecordingLog(if name == "test" {
+                    "test"
+                } else {
+                    "unknown"
+                }))
+            })
+            .configure_services(|collection, context| {
+                let level = context.configuration.get_parsed::<u8>("Level").unwrap();
+                collection.register_instance(level);
+            })
+            .add_hosted::<Worker>()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            Some(Environment::Development),
+            host.provider().get::<Environment>()
+        );
+        assert_eq!(Some(1u8), host.provider().get::<u8>());
+        host.run_until(|_| ());
+        assert_eq!(
+            vec!["test Info start", "test Info stop"],
+            *MESSAGES.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn default_log_level_depends_on_environment() {
+        let log_level = |environment, source: [(&'static str, &'static str); 1]| {
+            default_log_level(&HostContext {
+                environment,
+                configuration: Arc::new(Configuration::load([&source as &dyn ConfigurationSource])),
+            })
+        };
+        assert_eq!(
+            LogLevel::Debug,
+            log_level(Environment::Development, [("", "")])
+        );
+        assert_eq!(
+            LogLevel::Info,
+            log_level(Environment::Production, [("", "")])
+        );
+        assert_eq!(
+            LogLevel::Error,
+            log_level(Environment::Development, [("Logging:LogLevel", "Error")])
+        );
+    }
+}
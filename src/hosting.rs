@@ -0,0 +1,215 @@
+use {
+    crate::{BuildError, Registered, ServiceCollection, ServiceProvider},
+    alloc::{sync::Arc, vec::Vec},
+    core::any::Any,
+};
+
+/// Background component, which is started after the ServiceProvider of a `Host` is built
+/// and stopped before it is dropped. Register it with `ServiceCollection::add_hosted()`
+pub trait HostedService: Send + Sync {
+    fn start(&self);
+    fn stop(&self);
+}
+
+impl ServiceCollection {
+    /// Registers the shared service `Arc<T>` as `Arc<dyn HostedService>`, so it's started by `Host`.
+    /// `Arc<T>` has to be registered separately, otherwise building fails with `BuildError::MissingDependency`
+    /// ```
+    /// use {ioc_rs::{Host, HostedService, ServiceCollection}, std::sync::{Arc, Mutex}};
+    ///
+    /// #[derive(Default)]
+    /// struct Worker(Mutex<Vec<&'static str>>);
+    /// impl HostedService for Worker {
+    ///     fn start(&self) { self.0.lock().unwrap().push("start") }
+    ///     fn stop(&self) { self.0.lock().unwrap().push("stop") }
+    /// }
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.register_shared(|| Arc::new(Worker::default()));
+    /// collection.add_hosted::<Worker>();
+    ///
+    /// let host = Host::new(collection).expect("Configuration is valid");
+    /// host.run_until(|provider| {
+    ///     let worker = provider.get::<Arc<Worker>>().unwrap();
+    ///     assert_eq!(vec!["start"], *worker.0.lock().unwrap());
+    /// });
+    /// ```
+    #[track_caller]
+    pub fn add_hosted<T: HostedService + Any>(&mut self) {
+        self.with::<Registered<Arc<T>>>()
+            .register(|service| service as Arc<dyn HostedService>);
+    }
+}
+
+/// Owns a ServiceProvider and the lifecycle of all registered `HostedService`s.
+/// Services are started in registration order and stopped in reverse order. Started services
+/// are stopped when the Host is dropped at the latest.
+pub struct Host {
+    started: Vec<Arc<dyn HostedService>>,
+    provider: ServiceProvider,
+}
+
+impl Host {
+    /// Builds the ServiceProvider without starting any HostedService
+    pub fn new(collection: ServiceCollection) -> Result<Self, BuildError> {
+        Ok(Self {
+            started: Vec::new(),
+            provider: collection.build()?,
+        })
+    }
+
+    pub fn provider(&self) -> &ServiceProvider {
+        &self.provider
+    }
+
+    /// Starts all HostedServices in registration order. Has no effect, if they are already started
+    pub fn start(&mut self) {
+        if !self.started.is_empty() {
+            return;
+        }
+        for service in self.provider.get_all::<Arc<dyn HostedService>>() {
+            service.start();
+            self.started.push(service);
+        }
+    }
+
+    /// Stops all started HostedServices in reverse registration order
+    pub fn stop(&mut self) {
+        while let Some(service) = self.started.pop() {
+            service.stop();
+        }
+    }
+
+    /// Starts all HostedServices, runs `until` and stops them afterwards
+    pub fn run_until(mut self, until: impl FnOnce(&ServiceProvider)) {
+        self.start();
+        until(&self.provider);
+        self.stop();
+    }
+
+    /// Starts all HostedServices and stops them, when SIGINT or SIGTERM is received.
+    /// Waiting for signals is only supported on unix, other platforms return `ErrorKind::Unsupported`
+    #[cfg(feature = "std")]
+    pub fn run(self) -> std::io::Result<()> {
+        let mut shutdown = signals::ShutdownSignal::register()?;
+        self.run_until(|_| shutdown.wait());
+        Ok(())
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+mod signals {
+    use signal_hook::{
+        consts::{SIGINT, SIGTERM},
+        iterator::Signals,
+    };
+
+    pub struct ShutdownSignal(Signals);
+
+    impl ShutdownSignal {
+        /// Signals are registered before any HostedService is started, so no signal gets lost
+        pub fn register() -> std::io::Result<Self> {
+            Signals::new([SIGINT, SIGTERM]).map(Self)
+        }
+
+        pub fn wait(&mut self) {
+            self.0.forever().next();
+        }
+    }
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+mod signals {
+    pub enum ShutdownSignal {}
+
+    impl ShutdownSignal {
+        pub fn register() -> std::io::Result<Self> {
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+
+        pub fn wait(&mut self) {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Mutex};
+
+    struct Recorder(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl HostedService for Recorder {
+        fn start(&self) {
+            self.1.lock().unwrap().push(format!("start {}", self.0));
+        }
+        fn stop(&self) {
+            self.1.lock().unwrap().push(format!("stop {}", self.0));
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Log(Arc<Mutex<Vec<String>>>);
+
+    fn create_collection(log: &Log) -> ServiceCollection {
+        let mut collection = ServiceCollection::new();
+        collection.register_instance(log.clone());
+        collection
+            .with::<Registered<Log>>()
+            .register_shared(|log| Arc::new(Recorder("first", log.0)));
+        collection.add_hosted::<Recorder>();
+        collection
+            .with::<Registered<Log>>()
+            .register(|log| Arc::new(Recorder("second", log.0)) as Arc<dyn HostedService>);
+        collection
+    }
+
+    #[test]
+    fn start_in_registration_order_and_stop_in_reverse() {
+        let log = Log::default();
+        Host::new(create_collection(&log))
+            .unwrap()
+            .run_until(|_| log.0.lock().unwrap().push("running".into()));
+
+        assert_eq!(
+            vec![
+                "start first",
+                "start second",
+                "running",
+                "stop second",
+                "stop first"
+            ],
+            *log.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn stop_started_services_on_drop() {
+        let log = Log::default();
+        let mut host = Host::new(create_collection(&log)).unwrap();
+        host.start();
+        host.start();
+        drop(host);
+
+        assert_eq!(
+            vec!["start first", "start second", "stop second", "stop first"],
+            *log.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn fail_if_hosted_service_is_not_registered() {
+        let mut collection = ServiceCollection::new();
+        collection.add_hosted::<Recorder>();
+        assert!(matches!(
+            Host::new(collection),
+            Err(BuildError::MissingDependency(_))
+        ));
+    }
+}
@@ -0,0 +1,387 @@
+use {
+    crate::{ServiceBuildError, ServiceMetadata, ServiceProvider},
+    alloc::{vec, vec::Vec},
+};
+
+impl ServiceProvider {
+    /// Initializes the shared services at `positions` and all shared services they depend on.
+    /// Dependencies are initialized first, so a failing service is reported with its own name
+    /// instead of the name of the service depending on it
+    pub(crate) fn initialize_shared(&self, positions: &[usize]) -> Result<(), ServiceBuildError> {
+        for pos in dependency_order(&self.immutable_state.metadata, positions) {
+            let metadata = &self.immutable_state.metadata[pos];
+            if let Some(initializer) = metadata.initializer {
+                #[cfg(feature = "std")]
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| initializer(self, pos)))
+                    .map_err(|e| ServiceBuildError {
+                    service: metadata.type_name,
+                    error: crate::BuildError::FailedInitialization(alloc::format!(
+                        "{}: {}",
+                        metadata.type_name,
+                        panic_message(&*e)
+                    )),
+                })?;
+                #[cfg(not(feature = "std"))]
+                initializer(self, pos);
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes the shared services at `order`, which has to be returned by `eager_order()`.
+    /// Used by ServiceProviderFactory, which can't return errors, so panics are propagated
+    pub(crate) fn initialize_eager(&self, order: &[usize]) {
+        for &pos in order {
+            if let Some(initializer) = self.immutable_state.metadata[pos].initializer {
+                initializer(self, pos);
+            }
+        }
+    }
+}
+
+/// Positions of the shared services marked with `eager()` and the shared services they depend on in initialization order
+pub(crate) fn eager_order(metadata: &[ServiceMetadata]) -> Vec<usize> {
+    let eager: Vec<_> = metadata
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.initializer.is_some() && m.eager)
+        .map(|(i, _)| i)
+        .collect();
+    dependency_order(metadata, &eager)
+        .into_iter()
+        .filter(|pos| metadata[*pos].initializer.is_some())
+        .collect()
+}
+
+/// Returns `roots` and all their transitive dependencies, where each service is preceded by its dependencies.
+/// The dependencies must not contain cycles, which is ensured by `ServiceCollection::validate_producers()`
+pub(crate) fn dependency_order(metadata: &[ServiceMetadata], roots: &[usize]) -> Vec<usize> {
+    let mut visited = vec![false; metadata.len()];
+    let mut result = Vec::new();
+    // Contains the position of a service and the index of its next unvisited dependency
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for &root in roots {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        stack.push((root, 0));
+        while let Some(&(pos, next)) = stack.last() {
+            match metadata[pos].dependencies.get(next) {
+                Some(&dependency) => {
+                    stack.last_mut().unwrap().1 += 1;
+                    if !visited[dependency] {
+                        visited[dependency] = true;
+                        stack.push((dependency, 0));
+                    }
+                }
+                None => {
+                    result.push(pos);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Time which was needed to initialize a shared service in `ServiceProvider::warm_up_parallel()`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceTiming {
+    pub type_name: &'static str,
+    pub duration: std::time::Duration,
+}
+
+/// Returned by `ServiceProvider::warm_up_parallel()`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WarmUpReport {
+    /// All shared services in the order their initialization completed
+    pub services: Vec<ServiceTiming>,
+}
+
+#[cfg(feature = "std")]
+struct WarmUpState {
+    ready: alloc::collections::VecDeque<usize>,
+    remaining_dependencies: Vec<usize>,
+    pending: usize,
+    report: WarmUpReport,
+    panic: Option<alloc::boxed::Box<dyn core::any::Any + Send>>,
+}
+
+#[cfg(feature = "std")]
+impl ServiceProvider {
+    /// Initializes all shared services of this ServiceProvider using up to `threads` threads.
+    /// A shared service is initialized as soon as all shared services it depends on are initialized, so independent
+    /// services (e.g. doing I/O) are created concurrently. Inherited shared services are left to their own ServiceProvider.
+    ///
+    /// If a service panics, no further initializations are started and the panic is propagated
+    /// after all running initializations are finished.
+    /// ```
+    /// use {ioc_rs::{Registered, ServiceCollection}, std::sync::Arc};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.register_shared(|| Arc::new(1i32));
+    /// collection.register_shared(|| Arc::new(2i64));
+    /// collection
+    ///     .with::<(Registered<Arc<i32>>, Registered<Arc<i64>>)>()
+    ///     .register_shared(|(a, b)| Arc::new(*a as i128 + *b as i128));
+    /// let provider = collection.build().expect("Configuration is valid");
+    ///
+    /// let report = provider.warm_up_parallel(2);
+    /// assert_eq!(3, report.services.len());
+    /// assert_eq!("alloc::sync::Arc<i128>", report.services[2].type_name);
+    /// ```
+    pub fn warm_up_parallel(&self, threads: usize) -> WarmUpReport {
+        let metadata = &self.immutable_state.metadata;
+        let mut remaining_dependencies = vec![0; metadata.len()];
+        let mut dependents = vec![Vec::new(); metadata.len()];
+        let mut shared_count = 0;
+        for (pos, service) in metadata.iter().enumerate() {
+            if service.initializer.is_some() {
+                shared_count += 1;
+                for dependency in shared_dependencies(metadata, pos) {
+                    remaining_dependencies[pos] += 1;
+                    dependents[dependency].push(pos);
+                }
+            }
+        }
+        let ready = metadata
+            .iter()
+            .enumerate()
+            .filter(|(pos, service)| {
+                service.initializer.is_some() && remaining_dependencies[*pos] == 0
+            })
+            .map(|(pos, _)| pos)
+            .collect();
+
+        let state = std::sync::Mutex::new(WarmUpState {
+            ready,
+            remaining_dependencies,
+            pending: shared_count,
+            report: WarmUpReport::default(),
+            panic: None,
+        });
+        let changed = std::sync::Condvar::new();
+
+        let worker = || loop {
+            let pos = {
+                let mut state = state.lock().unwrap();
+                loop {
+                    if state.panic.is_some() || state.pending == 0 {
+                        return;
+                    }
+                    if let Some(pos) = state.ready.pop_front() {
+                        break pos;
+                    }
+                    state = changed.wait(state).unwrap();
+                }
+            };
+            let service = &metadata[pos];
+            let start = std::time::Instant::now();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                (service.initializer.unwrap())(self, pos)
+            }));
+            let duration = start.elapsed();
+
+            let mut state = state.lock().unwrap();
+            match result {
+                Ok(()) => {
+                    state.pending -= 1;
+                    state.report.services.push(ServiceTiming {
+                        type_name: service.type_name,
+                        duration,
+                    });
+                    for &dependent in &dependents[pos] {
+                        state.remaining_dependencies[dependent] -= 1;
+                        if state.remaining_dependencies[dependent] == 0 {
+                            state.ready.push_back(dependent);
+                        }
+                    }
+                }
+                Err(e) => {
+                    state.panic = Some(e);
+                }
+            }
+            changed.notify_all();
+        };
+
+        std::thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(worker);
+            }
+        });
+
+        let state = state.into_inner().unwrap();
+        if let Some(panic) = state.panic {
+            std::panic::resume_unwind(panic);
+        }
+        state.report
+    }
+}
+
+/// Returns the positions of all shared services which are required to create the service at `pos`.
+/// Transient services are skipped, as they are created by the shared service requiring them
+#[cfg(feature = "std")]
+fn shared_dependencies(metadata: &[ServiceMetadata], pos: usize) -> Vec<usize> {
+    let mut visited = vec![false; metadata.len()];
+    let mut stack = metadata[pos].dependencies.clone();
+    let mut result = Vec::new();
+    while let Some(current) = stack.pop() {
+        if core::mem::replace(&mut visited[current], true) {
+            continue;
+        }
+        if metadata[current].initializer.is_some() {
+            result.push(current);
+        } else {
+            stack.extend_from_slice(&metadata[current].dependencies);
+        }
+    }
+    result
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn panic_message(payload: &(dyn core::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| {
+            payload
+                .downcast_ref::<alloc::string::String>()
+                .map(|s| s.as_str())
+        })
+        .unwrap_or("Box<dyn Any>")
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{BuildError, Registered, ServiceCollection, WeakServiceProvider},
+        alloc::sync::Arc,
+        core::sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    #[test]
+    fn build_eager_initializes_all_shared_services() {
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(CREATED.fetch_add(1, Ordering::SeqCst) as i32));
+        collection
+            .with::<Registered<Arc<i32>>>()
+            .register_shared(|_| Arc::new(CREATED.fetch_add(1, Ordering::SeqCst) as i64));
+        collection.register(|| CREATED.fetch_add(1, Ordering::SeqCst) as i8);
+
+        let provider = collection.build_eager().unwrap();
+        assert_eq!(2, CREATED.load(Ordering::SeqCst));
+        assert_eq!(0, *provider.get::<Arc<i32>>().unwrap());
+        assert_eq!(1, *provider.get::<Arc<i64>>().unwrap());
+    }
+
+    #[test]
+    fn build_initializes_eager_services_after_their_dependencies() {
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(CREATED.fetch_add(1, Ordering::SeqCst) as i32));
+        collection
+            .with::<Registered<i16>>()
+            .register_shared(|_| Arc::new(CREATED.fetch_add(1, Ordering::SeqCst) as i64))
+            .eager();
+        collection
+            .with::<Registered<Arc<i32>>>()
+            .register(|i| *i as i16);
+        collection.register_shared(|| Arc::new(CREATED.fetch_add(1, Ordering::SeqCst) as u8));
+
+        let provider = collection.build().unwrap();
+        assert_eq!(2, CREATED.load(Ordering::SeqCst));
+        assert_eq!(0, *provider.get::<Arc<i32>>().unwrap());
+        assert_eq!(1, *provider.get::<Arc<i64>>().unwrap());
+    }
+
+    #[test]
+    fn factory_initializes_eager_services_of_each_provider() {
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<Registered<i32>>()
+            .register_shared(|base| {
+                CREATED.fetch_add(1, Ordering::SeqCst);
+                Arc::new(base as i64)
+            })
+            .eager();
+        let factory = collection.build_factory::<i32>().unwrap();
+
+        let first = factory.build(1);
+        assert_eq!(1, CREATED.load(Ordering::SeqCst));
+        let second = factory.build(2);
+        assert_eq!(2, CREATED.load(Ordering::SeqCst));
+        assert_eq!(1, *first.get::<Arc<i64>>().unwrap());
+        assert_eq!(2, *second.get::<Arc<i64>>().unwrap());
+        assert_eq!(2, CREATED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn build_without_eager_services_initializes_nothing() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared::<i32>(|| panic!("Should never be called"));
+        assert!(collection.build().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn warm_up_parallel_initializes_dependencies_first() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            Arc::new(1i32)
+        });
+        collection.register_shared(|| Arc::new(2i64));
+        collection
+            .with::<Registered<Arc<i32>>>()
+            .register(|i| *i as i16);
+        collection
+            .with::<(Registered<i16>, Registered<Arc<i64>>)>()
+            .register_shared(|(a, b)| Arc::new(a as i128 + *b as i128));
+        let provider = collection.build().unwrap();
+
+        let report = provider.warm_up_parallel(4);
+        let names: alloc::vec::Vec<_> = report.services.iter().map(|s| s.type_name).collect();
+        assert_eq!(3, names.len());
+        assert_eq!("alloc::sync::Arc<i128>", names[2]);
+        let slow = report
+            .services
+            .iter()
+            .find(|s| s.type_name == "alloc::sync::Arc<i32>");
+        assert!(slow.unwrap().duration >= std::time::Duration::from_millis(20));
+        assert_eq!(Some(3), provider.get::<Arc<i128>>().map(|i| *i));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(expected = "Invalid configuration")]
+    fn warm_up_parallel_propagates_panics() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared::<i32>(|| panic!("Invalid configuration"));
+        collection
+            .with::<Registered<Arc<i32>>>()
+            .register_shared(|_| Arc::new(1i64));
+        let provider = collection.build().unwrap();
+        provider.warm_up_parallel(2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn failing_dependency_of_eager_service_is_reported_with_its_own_type() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared::<i32>(|| panic!("Invalid configuration"));
+        collection
+            .with::<(WeakServiceProvider, Registered<Arc<i32>>)>()
+            .register_shared(|_| Arc::new(1i64))
+            .eager();
+
+        assert_eq!(
+            BuildError::FailedInitialization("alloc::sync::Arc<i32>: Invalid configuration".into()),
+            collection.build().unwrap_err()
+        );
+    }
+}
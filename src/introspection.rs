@@ -0,0 +1,246 @@
+use {
+    crate::{Registered, ServiceLifetime, ServiceMetadata, ServiceProvider},
+    alloc::{sync::Arc, vec::Vec},
+    core::any::{Any, TypeId},
+};
+
+/// Registration of a ServiceProvider or one of its parents returned by `ServiceProvider::registrations()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceRegistration {
+    pub type_name: &'static str,
+    /// TypeId of the service, e.g. `TypeId::of::<Arc<T>>()` for shared services
+    pub type_id: TypeId,
+    pub lifetime: ServiceLifetime,
+    /// Whether the shared service was created already. None for services, which aren't shared
+    pub initialized: Option<bool>,
+    /// Registered in a parent ServiceProvider
+    pub inherited: bool,
+}
+
+impl ServiceProvider {
+    /// Checks if `T` is registered in this ServiceProvider or one of its parents without creating it:
+    /// ```
+    /// use {ioc_rs::ServiceCollection, std::sync::Arc};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.register_shared(|| Arc::new(42));
+    /// collection.register(|| 1u8);
+    /// collection.register(|| 2u8);
+    /// let provider = collection.build().expect("Configuration is valid");
+    ///
+    /// assert!(provider.contains::<Arc<i32>>());
+    /// assert!(!provider.contains::<i32>());
+    /// assert_eq!(2, provider.count::<u8>());
+    /// ```
+    pub fn contains<T: Any>(&self) -> bool {
+        self.immutable_state
+            .service_types()
+            .last(TypeId::of::<Registered<T>>())
+            .is_some()
+    }
+
+    /// Returns the number of services `get_all::<T>()` would return
+    pub fn count<T: Any>(&self) -> usize {
+        self.immutable_state
+            .service_types()
+            .positions(TypeId::of::<Registered<T>>())
+            .count()
+    }
+
+    /// Registrations of the parents in the order they were added, followed by the own registrations.
+    /// Like `describe()`, registrations of each ServiceProvider are ordered by type name
+    pub fn registrations(&self) -> Vec<ServiceRegistration> {
+        let mut registrations = Vec::new();
+        self.visit_registrations(|provider, metadata, inherited| {
+            registrations.push(ServiceRegistration {
+                type_name: metadata.type_name,
+                type_id: metadata.type_id,
+                lifetime: metadata.lifetime,
+                initialized: metadata
+                    .shared_state
+                    .map(|pos| provider.service_states.shared_services[pos].get().is_some()),
+                inherited,
+            })
+        });
+        registrations
+    }
+
+    /// Visits the registrations of all ancestors in the order they were added, followed by the own registrations.
+    /// Ancestors reachable through multiple parents are only visited the first time.
+    /// Registrations of the same ServiceProvider are ordered by type name, so the order doesn't change between builds
+    pub(crate) fn visit_registrations(
+        &self,
+        mut visitor: impl FnMut(&ServiceProvider, &ServiceMetadata, bool),
+    ) {
+        let mut providers = Vec::new();
+        self.collect_ancestors(&mut providers);
+        for provider in providers {
+            let mut own: Vec<_> = provider.immutable_state.metadata.iter().collect();
+            // Registrations of the same type keep their registration order, as the sort is stable
+            own.sort_by_key(|metadata| metadata.type_name);
+            let inherited = !core::ptr::eq(provider, self);
+            for metadata in own {
+                visitor(provider, metadata, inherited);
+            }
+        }
+    }
+
+    /// Adds the ancestors and this ServiceProvider itself after its parents, unless they were added before
+    fn collect_ancestors<'a>(&'a self, providers: &mut Vec<&'a ServiceProvider>) {
+        for parent in &self.immutable_state.parents {
+            parent.0.collect_ancestors(providers);
+        }
+        if !providers
+            .iter()
+            .any(|p| Arc::ptr_eq(&p.service_states, &self.service_states))
+        {
+            providers.push(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{Registered, ServiceCollection},
+        alloc::sync::Arc,
+    };
+
+    #[test]
+    fn registrations_report_initialized_shared_services_of_parents() {
+        let mut parent = ServiceCollection::new();
+        parent.register_shared(|| Arc::new(1i32));
+        parent.register_shared(|| Arc::new(1u8));
+        let parent = parent.build().unwrap();
+        parent.get::<Arc<i32>>();
+
+        let mut child = ServiceCollection::new();
+        child.with::<Registered<Arc<i32>>>().register(|i| *i as i64);
+        let child = child
+            .with_parent(&parent)
+            .build_factory::<u16>()
+            .unwrap()
+            .build(1);
+
+        let registrations: Vec<_> = child
+            .registrations()
+            .into_iter()
+            .map(|r| (r.type_name, r.lifetime, r.initialized, r.inherited))
+            .collect();
+        assert_eq!(
+            alloc::vec![
+                (
+                    "alloc::sync::Arc<i32>",
+                    ServiceLifetime::Shared,
+                    Some(true),
+                    true
+                ),
+                (
+                    "alloc::sync::Arc<u8>",
+                    ServiceLifetime::Shared,
+                    Some(false),
+                    true
+                ),
+                ("i64", ServiceLifetime::Transient, None, false),
+                ("u16", ServiceLifetime::Base, None, false),
+            ],
+            registrations
+        );
+        assert_eq!(TypeId::of::<i64>(), child.registrations()[2].type_id);
+        assert!(child.contains::<Arc<u8>>());
+        assert_eq!(1, child.count::<u16>());
+        assert_eq!(0, child.count::<u32>());
+    }
+
+    #[test]
+    fn ancestors_shared_by_multiple_parents_are_listed_once() {
+        let mut root = ServiceCollection::new();
+        root.register(|| 1u8);
+        let root = root.build().unwrap();
+        let mut first = ServiceCollection::new();
+        first.register(|| 1u16);
+        let first = first
+            .with_parent(&root)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+        let mut second = ServiceCollection::new();
+        second.register(|| 1u32);
+        let second = second
+            .with_parent(&root)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        let child = ServiceCollection::new()
+            .with_parent(&first)
+            .and_parent(&second)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        let registrations: Vec<_> = child
+            .registrations()
+            .into_iter()
+            .map(|r| (r.type_name, r.inherited))
+            .collect();
+        assert_eq!(
+            alloc::vec![
+                ("u8", true),
+                ("()", true),
+                ("u16", true),
+                ("()", true),
+                ("u32", true),
+                ("()", false),
+            ],
+            registrations
+        );
+        let manifest: Vec<_> = child
+            .describe()
+            .registrations
+            .into_iter()
+            .map(|r| (r.type_name, r.inherited))
+            .collect();
+        assert_eq!(registrations, manifest);
+    }
+
+    #[test]
+    fn count_agrees_with_registrations_of_ancestors_shared_by_multiple_parents() {
+        let mut root = ServiceCollection::new();
+        root.register(|| 1u8);
+        let root = root.build().unwrap();
+        let mut first = ServiceCollection::new();
+        first.register(|| 2u8);
+        let first = first
+            .with_parent(&root)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+        let second = ServiceCollection::new()
+            .with_parent(&root)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        let child = ServiceCollection::new()
+            .with_parent(&first)
+            .and_parent(&second)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        let registered = child
+            .registrations()
+            .iter()
+            .filter(|r| r.type_name == "u8")
+            .count();
+        assert_eq!(2, registered);
+        assert_eq!(registered, child.count::<u8>());
+        assert_eq!(
+            alloc::vec![1, 2],
+            child.get_all::<u8>().collect::<alloc::vec::Vec<_>>()
+        );
+        assert_eq!(Some(2), child.get::<u8>());
+    }
+}
@@ -0,0 +1,83 @@
+use {
+    crate::LeakReport,
+    core::sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Null as long as the default error handler is used
+static ERROR_HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Handles lifetime errors, which cannot be enforced using the type system. This is the case when:
+/// - WeakServiceProvider outlives the ServiceProvider its created from
+/// - ServiceIterator<T>, which owns a WeakServiceProvider internally, outlives its ServiceProvider
+/// - Any shared service outlives its ServiceProvider
+///
+/// Ignoring errors is strongly discouraged, but doesn't cause any undefined behavior or memory leaks by the framework.
+/// However, leaking context specific services often lead to memory leaks in user code which are difficult to find:
+/// All shared references of a ServiceProvider are kept alive if the result of a single provider::get::<AllRegistered<i32>>() call
+/// is leaking it's provider. This can easily happen, if you forget to collect the results into a vector.
+/// To prevent these sneaky errors, ServiceProvider::drop() ensures that none of it's internals are kept alive when debug_assertions are enabled.
+///
+/// The handler is used by all ServiceProviders without a `LeakPolicy`. The default implementation panics,
+/// if the std-feature is enabled (on by default). Otherwise this is a no_op.
+/// For custom implementations, be aware that this function could be called while panicking already.
+/// In std, panic!(), when the thread is panicking already, terminates the entire program immediately.
+pub fn set_error_handler(handler: fn(&LeakReport)) {
+    ERROR_HANDLER.store(handler as *mut (), Ordering::SeqCst);
+}
+
+pub(crate) fn handle_error(report: &LeakReport) {
+    let handler = ERROR_HANDLER.load(Ordering::SeqCst);
+    if handler.is_null() {
+        #[cfg(feature = "std")]
+        LeakPolicy::Panic.handle(report);
+    } else {
+        // Only fn(&LeakReport) are stored by set_error_handler
+        let handler = unsafe { core::mem::transmute::<*mut (), fn(&LeakReport)>(handler) };
+        handler(report);
+    }
+}
+
+/// Defines how a ServiceProvider reacts to services, WeakServiceProviders and ServiceIterators outliving it.
+/// Unlike the global error handler, which is only used if debug_assertions are enabled, a ServiceProvider
+/// with a LeakPolicy checks for leaks in all build profiles.
+/// ```
+/// use {ioc_rs::{LeakPolicy, ServiceCollection}, std::sync::Arc};
+///
+/// let mut collection = ServiceCollection::new();
+/// collection.register_shared(|| Arc::new(42));
+/// collection.set_leak_policy(LeakPolicy::Callback(|report| {
+///     assert_eq!("i32", report.services[0].typename);
+/// }));
+/// let provider = collection.build().expect("Configuration is valid");
+/// let leaking = provider.get::<Arc<i32>>();
+/// drop(provider);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub enum LeakPolicy {
+    /// Panics with the LeakReport, unless the thread is panicking already
+    Panic,
+    /// Writes the LeakReport to stderr. Requires the `std` feature, leaks are ignored otherwise
+    Log,
+    Ignore,
+    Callback(fn(&LeakReport)),
+}
+
+impl LeakPolicy {
+    pub(crate) fn handle(&self, report: &LeakReport) {
+        match self {
+            LeakPolicy::Panic => {
+                #[cfg(feature = "std")]
+                if std::thread::panicking() {
+                    return;
+                }
+                panic!("{:?}", report)
+            }
+            LeakPolicy::Log => {
+                #[cfg(feature = "std")]
+                std::eprintln!("{:?}", report);
+            }
+            LeakPolicy::Ignore => {}
+            LeakPolicy::Callback(callback) => callback(report),
+        }
+    }
+}
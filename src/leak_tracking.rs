@@ -0,0 +1,120 @@
+use {
+    alloc::{collections::BTreeMap, vec::Vec},
+    core::panic::Location,
+    std::cell::RefCell,
+};
+
+/// Describes where a reference to a ServiceProvider or one of its shared services was obtained.
+/// Available with the `track-leaks` feature
+#[derive(Clone, PartialEq, Eq)]
+pub struct LeakOrigin {
+    /// Shared service, `WeakServiceProvider` or `ServiceIterator` which was obtained
+    pub typename: &'static str,
+    /// Service whose factory requested it. None, if it was requested directly from a ServiceProvider
+    pub requested_by: Option<&'static str>,
+    /// Location of the call to `get()`, `get_all()`, `resolve_unchecked()`, `with_parent()` or `WeakServiceProvider::clone()`
+    /// which obtained it. None, if it was obtained outside of these methods (e.g. `WeakServiceProvider::from(&provider)`)
+    pub location: Option<&'static Location<'static>>,
+}
+
+impl core::fmt::Debug for LeakOrigin {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.typename)?;
+        if let Some(requested_by) = self.requested_by {
+            write!(f, " requested by {}", requested_by)?;
+        }
+        if let Some(location) = self.location {
+            write!(f, " at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+enum Frame {
+    Call(&'static Location<'static>),
+    Service(&'static str),
+}
+
+std::thread_local! {
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Removes the frame it was created for when it's dropped
+pub struct FrameGuard(());
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        FRAMES.with(|frames| {
+            frames.borrow_mut().pop();
+        });
+    }
+}
+
+/// Used by public methods annotated with `#[track_caller]`
+pub fn enter_call(location: &'static Location<'static>) -> FrameGuard {
+    FRAMES.with(|frames| frames.borrow_mut().push(Frame::Call(location)));
+    FrameGuard(())
+}
+
+/// Used while the dependencies of a service are resolved and the service is created
+pub fn enter_service(type_name: &'static str) -> FrameGuard {
+    FRAMES.with(|frames| frames.borrow_mut().push(Frame::Service(type_name)));
+    FrameGuard(())
+}
+
+/// Describes, who is obtaining `typename` on the current thread
+pub fn current_origin(typename: &'static str) -> LeakOrigin {
+    FRAMES.with(|frames| {
+        let frames = frames.borrow();
+        LeakOrigin {
+            typename,
+            requested_by: frames.iter().rev().find_map(|f| match f {
+                Frame::Service(name) => Some(*name),
+                Frame::Call(_) => None,
+            }),
+            location: frames.iter().rev().find_map(|f| match f {
+                Frame::Call(location) => Some(*location),
+                Frame::Service(_) => None,
+            }),
+        }
+    })
+}
+
+/// Origins of all references handed out by a ServiceProvider
+#[derive(Default)]
+pub struct LeakOrigins {
+    next_provider_id: usize,
+    /// Currently alive WeakServiceProviders and ServiceIterators
+    providers: BTreeMap<usize, LeakOrigin>,
+    /// Indexed by the position of the shared service state. Clones of an Arc cannot be tracked individually,
+    /// so every distinct origin is kept until the ServiceProvider is dropped
+    services: BTreeMap<usize, Vec<LeakOrigin>>,
+}
+
+impl LeakOrigins {
+    pub fn add_provider(&mut self, origin: LeakOrigin) -> usize {
+        let id = self.next_provider_id;
+        self.next_provider_id += 1;
+        self.providers.insert(id, origin);
+        id
+    }
+
+    pub fn remove_provider(&mut self, id: usize) {
+        self.providers.remove(&id);
+    }
+
+    pub fn add_service(&mut self, pos: usize, origin: LeakOrigin) {
+        let origins = self.services.entry(pos).or_default();
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &LeakOrigin> {
+        self.providers.values()
+    }
+
+    pub fn services(&self, pos: usize) -> &[LeakOrigin] {
+        self.services.get(&pos).map(|x| x.as_slice()).unwrap_or(&[])
+    }
+}
@@ -15,14 +15,24 @@
 //! - Register Types/Traits which are not part of your crate (e.g. std::*). No macros needed.
 //! - Service registration from separately compiled dynamic libraries. see `examples/distributed_simple` for more details
 //! - Transient services are retrieved as `T` without any additional frills, SharedServices as `Arc<T>`
-//! - Inheritance instead of scoped services (Service requests can be delegated to parent `ServiceProvider`s)
+//! - Shared services are disposed in reverse creation order, calling hooks registered with `on_dispose()`
+//! - Background components implementing `HostedService` are started and stopped by a `Host` (`host.run()` waits for SIGINT/SIGTERM)
+//! - `HostBuilder` wires configuration sources, logging, the environment (Development/Production) and hosted services, so every binary starts the same way
+//! - Health checks registered with `add_health_check()` are aggregated into a `HealthReport` with timeouts
+//! - Inheritance instead of scoped services (Service requests can be delegated to parent `ServiceProvider`s). Children can decorate services of their parents with `Inherited<T>`, unless the parent `sealed()` them
 //! - Service discovery (`provider.get_all::<MyService>()` returns an iterator, which lazily generates all registered `MyService` instances)
+//! - `provider.describe()` lists all registrations in a manifest, which is serializable with the `serde` feature
+//! - `provider.contains::<T>()`, `count::<T>()` and `registrations()` inspect a `ServiceProvider` without creating services
+//! - The dependency graph of a `ServiceCollection` or `ServiceProvider` can be rendered with Graphviz (`dependency_graph().to_dot()`)
+//! - `collection.analyze()` warns about shadowed registrations, shared services capturing transient ones and unused registrations
 //! - Fail fast. When building a `ServiceProvider` all registered services are checked to
 //!   - have all dependencies
 //!   - contain no dependency-cycles
+//!   - contain the services declared with `require()`, even if no other service depends on them
+//!   - optionally be instantiable, for shared services marked with `eager()` or when using `build_eager()`
 //! - Common pitfalls of traditional IOC are prevented by design
 //!   - Singleton services cannot reference scoped services, as scoped services don't exist
-//!   - Shared services cannot outlive their `ServiceProvider` (checked at runtime when debug_assertions are enabled)
+//!   - Shared services cannot outlive their `ServiceProvider` (checked at runtime when debug_assertions are enabled or when calling `close()`). The `track-leaks` feature records where leaking references were obtained
 //! - `#[no_std]`
 //!
 //! Visit the examples/documentation for more details
@@ -34,60 +44,75 @@
 extern crate alloc;
 
 use {
-    alloc::{
-        boxed::Box,
-        collections::BTreeMap,
-        string::{String, ToString},
-        sync::Arc,
-        vec,
-        vec::Vec,
-    },
+    alloc::{boxed::Box, collections::BTreeMap, string::String, sync::Arc, vec::Vec},
     core::{
         any::{type_name, Any, TypeId},
         fmt::Debug,
         marker::PhantomData,
+        mem::ManuallyDrop,
+        panic::Location,
     },
     once_cell::sync::OnceCell,
+    resolvable::{Dependency, ServicePositions, ServiceTypes},
     service_provider_factory::ServiceProviderFactoryBuilder,
     untyped::{UntypedFn, UntypedPointer},
 };
 
+mod analysis;
 mod binary_search;
+mod configuration;
+mod dependency_graph;
+#[cfg(feature = "std")]
+mod health;
+mod host_builder;
+mod hosting;
+mod initialization;
+mod introspection;
+mod leak_policy;
+#[cfg(feature = "track-leaks")]
+mod leak_tracking;
+mod logging;
+mod manifest;
+#[cfg(feature = "std")]
+mod resolution_stack;
 mod resolvable;
 mod service_provider_factory;
+#[cfg(feature = "std")]
+mod state_pool;
 mod untyped;
 
-pub use resolvable::Resolvable;
-pub use service_provider_factory::ServiceProviderFactory;
-
-/// Handles lifetime errors, which cannot be enforced using the type system. This is the case when:
-/// - WeakServiceProvider outlives the ServiceProvider its created from
-/// - ServiceIterator<T>, which owns a WeakServiceProvider internally, outlives its ServiceProvider
-/// - Any shared service outlives its ServiceProvider
-///
-/// Ignoring errors is strongly discouraged, but doesn't cause any undefined behavior or memory leaks by the framework.
-/// However, leaking context specific services often lead to memory leaks in user code which are difficult to find:
-/// All shared references of a ServiceProvider are kept alive if the result of a single provider::get::<AllRegistered<i32>>() call
-/// is leaking it's provider. This can easily happen, if you forget to collect the results into a vector.
-/// To prevent these sneaky errors, ServiceProvider::drop() ensures that none of it's internals are kept alive when debug_assertions are enabled.
-///
-/// The default implementation panics, if the std-feature is enabled (on by default). Otherwise this is a no_op
-/// For custom implementations, be aware that this function could be called while panicking already.
-/// In std, panic!(), when the thread is panicking already, terminates the entire program immediately.
-///
-/// This variable only exists, if debug_assertions are enabled
-#[cfg(debug_assertions)]
-pub static mut ERROR_HANDLER: fn(msg: &dyn core::fmt::Debug) = |msg| {
-    #[cfg(feature = "std")]
-    if !std::thread::panicking() {
-        panic!("{:?}", msg)
-    }
+pub use analysis::{Diagnostic, DiagnosticKind, Severity};
+#[cfg(feature = "std")]
+pub use configuration::EnvironmentVariables;
+pub use configuration::{Configuration, ConfigurationSource};
+pub use dependency_graph::{DependencyGraph, GraphEdge, GraphNode, GraphNodeKind};
+#[cfg(feature = "std")]
+pub use health::{
+    HealthCheck, HealthCheckOptions, HealthCheckResult, HealthReport, HealthReportEntry,
+    HealthStatus,
 };
+#[cfg(feature = "std")]
+pub use host_builder::HostError;
+pub use host_builder::{Environment, HostBuilder, HostContext};
+pub use hosting::{Host, HostedService};
+#[cfg(feature = "std")]
+pub use initialization::{ServiceTiming, WarmUpReport};
+pub use introspection::ServiceRegistration;
+pub use leak_policy::{set_error_handler, LeakPolicy};
+#[cfg(feature = "track-leaks")]
+pub use leak_tracking::LeakOrigin;
+#[cfg(feature = "std")]
+pub use logging::ConsoleLog;
+pub use logging::{Log, LogLevel};
+pub use manifest::{DependencyDescriptor, RegistrationDescriptor, ServiceManifest};
+pub use resolvable::{DependencyKind, Resolvable};
+pub use service_provider_factory::{BaseValues, ServiceProviderFactory};
+pub use untyped::LeakedService;
 
 /// Type used to retrieve all instances `T` of a `ServiceProvider`.
 /// Services are built just in time when calling `next()`
 pub struct ServiceIterator<T> {
-    next_pos: Option<usize>,
+    positions: ServicePositions,
     provider: WeakServiceProvider,
     item_type: PhantomData<T>,
 }
@@ -98,50 +123,324 @@ pub struct Registered<T: Any>(PhantomData<T>);
 /// Represents a query for all registered instances of Type `T`.
 pub struct AllRegistered<T: Any>(PhantomData<T>);
 
+/// Represents a query for the last instance of `T` registered in a parent ServiceProvider passed to `with_parent()`.
+/// Allows children to decorate services of their parents without creating a cyclic dependency:
+/// ```
+/// use {ioc_rs::{Inherited, ServiceCollection}, std::sync::Arc};
+///
+/// trait Cache: Send + Sync { fn get(&self) -> String; }
+/// struct MemoryCache;
+/// impl Cache for MemoryCache { fn get(&self) -> String { "cached".into() } }
+/// struct LoggingCache(Arc<dyn Cache>);
+/// impl Cache for LoggingCache { fn get(&self) -> String { format!("logged {}", self.0.get()) } }
+///
+/// let mut parent = ServiceCollection::new();
+/// parent.register_shared(|| Arc::new(MemoryCache)).alias(|c| c as Arc<dyn Cache>);
+/// let parent = parent.build().expect("Configuration is valid");
+///
+/// let mut child = ServiceCollection::new();
+/// child
+///     .with::<Inherited<Arc<dyn Cache>>>()
+///     .register(|inner| Arc::new(LoggingCache(inner)) as Arc<dyn Cache>);
+/// let factory = child.with_parent(&parent).build_factory().expect("Configuration is valid");
+///
+/// let provider = factory.build(());
+/// assert_eq!("logged cached", provider.get::<Arc<dyn Cache>>().unwrap().get());
+/// ```
+pub struct Inherited<T: Any>(PhantomData<T>);
+
 /// Collection of constructors for different types of services. Registered constructors are never called in this state.
 /// Instances can only be received by a ServiceProvider, which can be created by calling `build`
 pub struct ServiceCollection {
     producer_factories: Vec<ServiceProducer>,
+    leak_policy: Option<LeakPolicy>,
+    /// Attributed to all services registered after calling `set_origin()`
+    origin: Option<&'static str>,
+    /// Services passed to `require()` or `require_all()`
+    requirements: Vec<Requirement>,
+}
+
+struct Requirement {
+    /// TypeId of `Registered<T>`
+    type_id: TypeId,
+    min_count: usize,
+    missing: MissingDependencyType,
 }
 
 
 pub struct AliasBuilder<'a, T: ?Sized>(&'a mut ServiceCollection, PhantomData<T>);
 
 impl<'a, T: Any> AliasBuilder<'a, T> {
-    pub fn alias<TNew: Any>(self, creator: fn(T) -> TNew) {
-        self.0.with::<Registered<T>>()
-            .register(creator);
+    /// Registers `TNew` created from this service, e.g. to resolve a shared service as trait object.
+    /// Returns the builder of `TNew`, so the alias can be sealed or aliased again
+    #[track_caller]
+    pub fn alias<TNew: Any>(self, creator: fn(T) -> TNew) -> AliasedBuilder<'a, TNew> {
+        self.0.register_alias(creator);
+        AliasedBuilder(self.0, PhantomData)
+    }
+}
+
+impl<'a, T: ?Sized> AliasBuilder<'a, T> {
+    /// Prevents child factories created with `with_parent()` from registering the same service,
+    /// which would shadow this registration. Building such a child returns `BuildError::OverrideOfSealedService`:
+    /// ```
+    /// use {ioc_rs::{BuildError, ServiceCollection}};
+    ///
+    /// let mut parent = ServiceCollection::new();
+    /// parent.register(|| "audited").sealed();
+    /// let parent = parent.build().expect("Configuration is valid");
+    ///
+    /// let mut child = ServiceCollection::new();
+    /// child.register(|| "not audited");
+    /// assert!(matches!(
+    ///     child.with_parent(&parent).build_factory::<()>(),
+    ///     Err(BuildError::OverrideOfSealedService(_))
+    /// ));
+    /// ```
+    pub fn sealed(self) -> Self {
+        self.seal(Sealing::Sealed)
+    }
+
+    /// Same as `sealed()`, but children may add services which are returned by `AllRegistered<T>`.
+    /// `Registered<T>` still resolves this registration in children
+    pub fn sealed_allowing_additions(self) -> Self {
+        self.seal(Sealing::SealedAllowingAdditions)
+    }
+
+    fn seal(self, sealing: Sealing) -> Self {
+        self.0.seal_last(sealing);
+        self
+    }
+}
+
+impl<'a, T: ?Sized> AliasBuilder<'a, Arc<T>> {
+    /// Instantiates the shared service and all shared services it depends on when `ServiceCollection::build()` is called,
+    /// instead of waiting for the first request. Has no effect on transient services returning an `Arc<T>`.
+    ///
+    /// ServiceProviderFactories instantiate eager services whenever they build a ServiceProvider.
+    /// As `ServiceProviderFactory::build()` can't return an error, panics of eager services are propagated
+    pub fn eager(self) -> Self {
+        if let Some(producer) = self.0.producer_factories.last_mut() {
+            producer.metadata.eager = true;
+        }
+        self
+    }
+}
+
+impl<'a, T: Any + ?Sized + Send + Sync> AliasBuilder<'a, Arc<T>> {
+    /// Registers a hook, which is called right before the ServiceProvider drops its reference to the shared service.
+    /// Shared services are disposed in reverse creation order when the ServiceProvider is dropped or closed,
+    /// so services are always disposed before the services they depend on:
+    /// ```
+    /// use {ioc_rs::{Registered, ServiceCollection}, std::sync::{Arc, Mutex}};
+    ///
+    /// static DISPOSED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection
+    ///     .register_shared(|| Arc::new(42u16))
+    ///     .on_dispose(|_pool| DISPOSED.lock().unwrap().push("pool"));
+    /// collection
+    ///     .with::<Registered<Arc<u16>>>()
+    ///     .register_shared(|pool| Arc::new(*pool as u32))
+    ///     .on_dispose(|_repository| DISPOSED.lock().unwrap().push("repository"));
+    ///
+    /// let provider = collection.build().expect("Configuration is valid");
+    /// assert_eq!(Some(42), provider.get::<Arc<u32>>().map(|r| *r));
+    /// provider.close().expect("No service outlives the provider");
+    /// assert_eq!(vec!["repository", "pool"], *DISPOSED.lock().unwrap());
+    /// ```
+    /// Has no effect on transient services returning an `Arc<T>`
+    pub fn on_dispose(self, hook: fn(&T)) -> Self {
+        if let Some(producer) = self.0.producer_factories.last_mut() {
+            producer.dispose_hook = Some(Box::new(move |pointer: &UntypedPointer| {
+                let service = unsafe { pointer.clone_as::<Arc<T>>() };
+                hook(&service)
+            }));
+        }
+        self
+    }
+}
+
+/// Returned by `alias()`. An alias is created from the aliased service whenever it's requested, so it can't be
+/// eager and has no dispose hook of its own. Both have to be configured on the aliased service instead:
+/// ```compile_fail
+/// use {ioc_rs::ServiceCollection, std::sync::Arc};
+///
+/// let mut collection = ServiceCollection::new();
+/// collection
+///     .register_shared(|| Arc::new(42u8))
+///     .alias(|value| value as Arc<dyn core::fmt::Debug + Send + Sync>)
+///     .eager();
+/// ```
+pub struct AliasedBuilder<'a, T: ?Sized>(&'a mut ServiceCollection, PhantomData<T>);
+
+impl<'a, T: Any> AliasedBuilder<'a, T> {
+    /// Registers `TNew` created from this alias, see `AliasBuilder::alias()`
+    #[track_caller]
+    pub fn alias<TNew: Any>(self, creator: fn(T) -> TNew) -> AliasedBuilder<'a, TNew> {
+        self.0.register_alias(creator);
+        AliasedBuilder(self.0, PhantomData)
+    }
+}
+
+impl<'a, T: ?Sized> AliasedBuilder<'a, T> {
+    /// Seals the alias, see `AliasBuilder::sealed()`
+    pub fn sealed(self) -> Self {
+        self.0.seal_last(Sealing::Sealed);
+        self
+    }
+
+    /// Seals the alias, see `AliasBuilder::sealed_allowing_additions()`
+    pub fn sealed_allowing_additions(self) -> Self {
+        self.0.seal_last(Sealing::SealedAllowingAdditions);
+        self
     }
 }
 
 struct ServiceProducer {
     type_id: TypeId,
     factory: UntypedFnFactory,
+    metadata: ServiceMetadata,
+    dispose_hook: Option<DisposeHook>,
 }
 
 impl ServiceProducer {
-    fn new<T: Any>(factory: UntypedFnFactory) -> Self {
-        Self::new_with_type(factory, TypeId::of::<Registered<T>>())
+    fn new<T: Any>(factory: UntypedFnFactory, lifetime: ServiceLifetime) -> Self {
+        Self::new_with_type(
+            factory,
+            TypeId::of::<Registered<T>>(),
+            ServiceMetadata::new::<T>(lifetime, None),
+        )
+    }
+    fn new_shared<T: Any>(factory: UntypedFnFactory) -> Self {
+        Self::new_with_type(
+            factory,
+            TypeId::of::<Registered<T>>(),
+            ServiceMetadata::new::<T>(ServiceLifetime::Shared, Some(initialize::<T>)),
+        )
     }
-    fn new_with_type(factory: UntypedFnFactory, type_id: TypeId) -> Self {
-        Self { type_id, factory }
+    fn new_with_type(
+        factory: UntypedFnFactory,
+        type_id: TypeId,
+        metadata: ServiceMetadata,
+    ) -> Self {
+        Self {
+            type_id,
+            factory,
+            metadata,
+            dispose_hook: None,
+        }
+    }
+    fn depending_on<TDep: Resolvable>(mut self) -> Self {
+        self.metadata.declared_dependencies = TDep::visit_dependencies;
+        self
+    }
+}
+
+/// Informations about a registered service, which are kept after building to initialize services in advance
+struct ServiceMetadata {
+    type_name: &'static str,
+    /// TypeId of the service itself, unlike `ServiceProducer::type_id`
+    type_id: TypeId,
+    lifetime: ServiceLifetime,
+    /// Module or plugin, which registered the service
+    origin: Option<&'static str>,
+    /// Call to `register*()`, which registered the service. None for bases of ServiceProviderFactories
+    registered_at: Option<&'static Location<'static>>,
+    /// Resolves the shared service at the passed position. None for transient services
+    initializer: Option<fn(&ServiceProvider, usize)>,
+    /// Position of the shared service state. None for services without state
+    shared_state: Option<usize>,
+    eager: bool,
+    sealing: Sealing,
+    /// Lifetime of the service, which was converted by `alias()` into this service
+    aliased: Option<ServiceLifetime>,
+    /// Positions of all services this service depends on
+    dependencies: Vec<usize>,
+    /// Visits the dependencies of the Resolvable passed to `ServiceCollection::with()`, even if they are missing
+    declared_dependencies: fn(&mut dyn FnMut(Dependency)),
+}
+
+impl ServiceMetadata {
+    fn new<T: Any>(
+        lifetime: ServiceLifetime,
+        initializer: Option<fn(&ServiceProvider, usize)>,
+    ) -> Self {
+        Self {
+            type_name: type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+            lifetime,
+            origin: None,
+            registered_at: None,
+            initializer,
+            shared_state: None,
+            eager: false,
+            sealing: Sealing::Open,
+            aliased: None,
+            dependencies: Vec::new(),
+            declared_dependencies: <() as resolvable::SealedResolvable>::visit_dependencies,
+        }
     }
 }
+
+/// Describes how a service is created and how long it lives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ServiceLifetime {
+    /// Created on each request by `register()`
+    Transient,
+    /// Created once per ServiceProvider by `register_shared()`
+    Shared,
+    /// Cloned from the value passed to `register_instance()`
+    Instance,
+    /// Resolved from the base passed to `ServiceProviderFactory::build()`
+    Base,
+}
+
+/// Defines, whether children are allowed to register a service which was registered by their parent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Sealing {
+    Open,
+    Sealed,
+    SealedAllowingAdditions,
+}
+
+/// pos must be the position of a producer for `T` within provider.producers
+fn initialize<T: Any>(provider: &ServiceProvider, pos: usize) {
+    let producer = &provider.immutable_state.producers[pos];
+    debug_assert_eq!(
+        producer.get_result_type_id(),
+        &TypeId::of::<Registered<T>>()
+    );
+    let factory = unsafe { producer.borrow_for::<T>() };
+    factory(provider);
+}
 // type CycleChecker = fn() -> Option<BuildError>;
+type DisposeHook = Box<dyn Fn(&UntypedPointer) + Send + Sync>;
 type UntypedFnFactory =
     Box<dyn for<'a> FnOnce(&mut UntypedFnFactoryContext<'a>) -> Result<UntypedFn, BuildError>>;
 
 struct UntypedFnFactoryContext<'a> {
     service_descriptor_pos: usize,
     state_counter: &'a mut usize,
-    final_ordered_types: &'a Vec<TypeId>,
-    cyclic_reference_candidates: &'a mut BTreeMap<usize, CycleCheckerValue>,
+    final_ordered_types: ServiceTypes<'a>,
+    cyclic_reference_candidates: &'a mut BTreeMap<usize, CycleCandidate>,
+    dependencies: Vec<usize>,
+    dispose_hook: Option<DisposeHook>,
+    dispose_hooks: &'a mut Vec<Option<DisposeHook>>,
+    registered_at: Option<&'static Location<'static>>,
+    shared_registered_at: &'a mut Vec<Option<&'static Location<'static>>>,
+    reserved_state: Option<usize>,
 }
 
 impl<'a> UntypedFnFactoryContext<'a> {
     fn reserve_state_space(&mut self) -> usize {
         let result: usize = *self.state_counter;
         *self.state_counter += 1;
+        self.dispose_hooks.push(self.dispose_hook.take());
+        self.shared_registered_at.push(self.registered_at);
+        self.reserved_state = Some(result);
         result
     }
     fn register_cyclic_reference_candidate(
@@ -149,12 +448,12 @@ impl<'a> UntypedFnFactoryContext<'a> {
         type_name: &'static str,
         dependencies: Box<dyn Iterator<Item = usize>>,
     ) {
+        self.dependencies = dependencies.collect();
         self.cyclic_reference_candidates.insert(
             self.service_descriptor_pos,
-            CycleCheckerValue {
-                is_visited: false,
+            CycleCandidate {
                 type_description: type_name,
-                iter: dependencies,
+                dependencies: self.dependencies.clone(),
             },
         );
     }
@@ -171,6 +470,104 @@ impl ServiceCollection {
     pub fn new() -> Self {
         Self {
             producer_factories: Vec::new(),
+            leak_policy: None,
+            origin: None,
+            requirements: Vec::new(),
+        }
+    }
+
+    /// Defines how ServiceProviders created from this collection handle leaks when they are dropped.
+    /// Without a LeakPolicy, leaks are only checked if debug_assertions are enabled and passed to the
+    /// handler configured with `set_error_handler()`
+    pub fn set_leak_policy(&mut self, policy: LeakPolicy) {
+        self.leak_policy = Some(policy);
+    }
+
+    /// Attributes all services registered afterwards to the module or plugin `origin` (e.g. `module_path!()`),
+    /// which is part of the manifest returned by `ServiceProvider::describe()`:
+    /// ```
+    /// use {ioc_rs::ServiceCollection};
+    ///
+    /// fn register_billing_plugin(collection: &mut ServiceCollection) {
+    ///     collection.set_origin("billing");
+    ///     collection.register(|| 42);
+    /// }
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// register_billing_plugin(&mut collection);
+    /// let manifest = collection.build().expect("Configuration is valid").describe();
+    /// assert_eq!(Some("billing"), manifest.registrations[0].origin);
+    /// ```
+    pub fn set_origin(&mut self, origin: &'static str) {
+        self.origin = Some(origin);
+    }
+
+    /// Fails building with `BuildError::MissingDependency`, if `T` isn't registered. Services which aren't
+    /// a dependency of other services (e.g. plugins resolved by the host) are validated as well:
+    /// ```
+    /// use {ioc_rs::{BuildError, ServiceCollection}, std::sync::Arc};
+    ///
+    /// trait Plugin {}
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.require::<Arc<dyn Plugin + Send + Sync>>();
+    ///
+    /// match collection.build() {
+    ///     Err(BuildError::MissingDependency(missing)) => {
+    ///         assert!(missing.name().contains("Plugin"));
+    ///         assert_eq!("ioc_rs::ServiceCollection", missing.requested_by());
+    ///     }
+    ///     _ => panic!("Expected the plugin to be missing"),
+    /// }
+    /// ```
+    /// Services of parents and bases of ServiceProviderFactories satisfy the requirement
+    #[track_caller]
+    pub fn require<T: Any>(&mut self) {
+        self.push_requirement::<T, Registered<T>>(1);
+    }
+
+    /// Same as `require()`, but fails if there are less than `min_count` registrations of `T`
+    #[track_caller]
+    pub fn require_all<T: Any>(&mut self, min_count: usize) {
+        self.push_requirement::<T, AllRegistered<T>>(min_count);
+    }
+
+    #[track_caller]
+    fn push_requirement<T: Any, TResolvable: Any>(&mut self, min_count: usize) {
+        self.requirements.push(Requirement {
+            type_id: TypeId::of::<Registered<T>>(),
+            min_count,
+            missing: MissingDependencyType {
+                requested_by: type_name::<ServiceCollection>(),
+                registered_at: Some(Location::caller()),
+                ..MissingDependencyType::new::<TResolvable>()
+            },
+        });
+    }
+
+    #[track_caller]
+    fn push_producer(&mut self, mut producer: ServiceProducer) {
+        producer.metadata.origin = self.origin;
+        producer.metadata.registered_at = Some(Location::caller());
+        self.producer_factories.push(producer);
+    }
+
+    /// Registers `TNew` created from the last registered service `T`, which is recorded as aliased service
+    #[track_caller]
+    fn register_alias<T: Any, TNew: Any>(&mut self, creator: fn(T) -> TNew) {
+        let aliased = self
+            .producer_factories
+            .last()
+            .map(|p| p.metadata.aliased.unwrap_or(p.metadata.lifetime));
+        self.with::<Registered<T>>().register(creator);
+        if let Some(producer) = self.producer_factories.last_mut() {
+            producer.metadata.aliased = aliased;
+        }
+    }
+
+    fn seal_last(&mut self, sealing: Sealing) {
+        if let Some(producer) = self.producer_factories.last_mut() {
+            producer.metadata.sealing = sealing;
         }
     }
 
@@ -204,26 +601,32 @@ impl ServiceCollection {
 
     /// Register an instance to be resolvable
     /// If a ServiceProviderFactory is used, all ServicesProviders will clone from the same origin
+    #[track_caller]
     pub fn register_instance<T: Clone + 'static + Send + Sync>(&mut self, instance: T) {
         let factory: UntypedFnFactory = Box::new(move |_service_state_counter| {
             let func: Box<dyn Fn(&ServiceProvider) -> T> =
                 Box::new(move |_: &ServiceProvider| instance.clone());
             Ok(func.into())
         });
-        self.producer_factories
-            .push(ServiceProducer::new::<T>(factory));
+        self.push_producer(ServiceProducer::new::<T>(
+            factory,
+            ServiceLifetime::Instance,
+        ));
     }
 
     /// Registers a transient service without dependencies.
     /// To add dependencies, use `with` to generate a ServiceBuilder.
+    #[track_caller]
     pub fn register<'a, T: Any>(&'a mut self, creator: fn() -> T) -> AliasBuilder<'a, T> {
         let factory: UntypedFnFactory = Box::new(move |_service_state_counter| {
             let func: Box<dyn Fn(&ServiceProvider) -> T> =
                 Box::new(move |_: &ServiceProvider| creator());
             Ok(func.into())
         });
-        self.producer_factories
-            .push(ServiceProducer::new::<T>(factory));
+        self.push_producer(ServiceProducer::new::<T>(
+            factory,
+            ServiceLifetime::Transient,
+        ));
         AliasBuilder(self, PhantomData)
     }
 
@@ -232,7 +635,11 @@ impl ServiceCollection {
     ///
     /// Shared services must have a reference count == 0 after dropping the ServiceProvider. If an Arc is
     /// cloned and thus kept alive, ServiceProvider::drop will panic to prevent service leaking in std.
-    pub fn register_shared<'a, T: Any + Send + Sync>(&'a mut self, creator: fn() -> Arc<T>) -> AliasBuilder<'a, Arc<T>> {
+    #[track_caller]
+    pub fn register_shared<'a, T: Any + Send + Sync>(
+        &'a mut self,
+        creator: fn() -> Arc<T>,
+    ) -> AliasBuilder<'a, Arc<T>> {
         let factory: UntypedFnFactory = Box::new(move |ctx| {
             let service_state_idx = ctx.reserve_state_space();
 
@@ -242,31 +649,95 @@ impl ServiceCollection {
                 });
             Ok(func.into())
         });
-        self.producer_factories
-            .push(ServiceProducer::new::<Arc<T>>(factory));
-            
+        self.push_producer(ServiceProducer::new_shared::<Arc<T>>(factory));
+
         AliasBuilder(self, PhantomData)
     }
 
     /// Checks, if all dependencies of registered services are available.
     /// If no errors occured, Ok(ServiceProvider) is returned.
+    ///
+    /// Shared services marked with `eager()` are instantiated before the ServiceProvider is returned.
     pub fn build(self) -> Result<ServiceProvider, BuildError> {
-        let (producers, types, service_states_count) = self.validate_producers(Vec::new())?;
-        let shared_services = vec![OnceCell::new(); service_states_count];
-        let immutable_state = Arc::new(ServiceProviderImmutableState {
-            producers,
-            types,
-            _parents: Vec::new(),
-        });
-        Ok(ServiceProvider {
-            immutable_state,
-            service_states: Arc::new(ServiceProviderMutableState {
-                shared_services,
-                base: None,
-            }),
-            #[cfg(debug_assertions)]
+        self.build_initialized(false)
+    }
+
+    /// Same as `build()`, but instantiates all shared services in dependency order before the ServiceProvider is returned.
+    /// If a shared service panics during its initialization, `BuildError::FailedInitialization` is returned
+    /// (requires the `std` feature, the panic is propagated otherwise)
+    /// ```
+    /// use {ioc_rs::{BuildError, ServiceCollection}, std::sync::Arc};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.register_shared(|| -> Arc<i32> { panic!("Invalid configuration") });
+    ///
+    /// match collection.build_eager() {
+    ///     Err(BuildError::FailedInitialization(msg)) => assert!(msg.contains("Invalid configuration")),
+    ///     _ => panic!("Expected initialization to fail"),
+    /// }
+    /// ```
+    pub fn build_eager(self) -> Result<ServiceProvider, BuildError> {
+        self.build_initialized(true)
+    }
+
+    /// Same as `build()`, but doesn't stop at the first error. All missing dependencies and cycles are reported
+    /// together with the service which requested them:
+    /// ```
+    /// use {ioc_rs::{BuildError, Registered, ServiceCollection}};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.with::<Registered<u8>>().register(|byte| byte as u16);
+    /// collection.with::<(Registered<i8>, Registered<i16>)>().register(|(a, b)| a as i32 + b as i32);
+    ///
+    /// let errors = collection.build_all_errors().err().expect("Dependencies are missing");
+    /// let services: Vec<_> = errors.errors.iter().map(|e| e.service).collect();
+    /// assert_eq!(vec!["u16", "i32", "i32"], services);
+    /// assert!(errors.errors.iter().all(|e| matches!(e.error, BuildError::MissingDependency(_))));
+    /// ```
+    pub fn build_all_errors(self) -> Result<ServiceProvider, BuildErrors> {
+        self.build_validated(false, true)
+    }
+
+    fn build_initialized(self, all_shared: bool) -> Result<ServiceProvider, BuildError> {
+        self.build_validated(all_shared, false)
+            .map_err(BuildErrors::into_first)
+    }
+
+    fn build_validated(
+        self,
+        all_shared: bool,
+        report_all: bool,
+    ) -> Result<ServiceProvider, BuildErrors> {
+        let leak_policy = self.leak_policy;
+        let (immutable_state, service_states_count) =
+            self.validate_producers(Vec::new(), report_all)?;
+        let provider = ServiceProvider {
+            immutable_state: Arc::new(immutable_state),
+            service_states: ManuallyDrop::new(Arc::new(ServiceProviderMutableState::new(
+                service_states_count,
+                None,
+            ))),
             is_root: true,
-        })
+            leak_policy,
+            #[cfg(feature = "std")]
+            pooled: false,
+            #[cfg(feature = "track-leaks")]
+            origin_id: None,
+        };
+        let eager_positions: Vec<_> = provider
+            .immutable_state
+            .metadata
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.initializer.is_some() && (all_shared || m.eager))
+            .map(|(i, _)| i)
+            .collect();
+        provider
+            .initialize_shared(&eager_positions)
+            .map_err(|error| BuildErrors {
+                errors: alloc::vec![error],
+            })?;
+        Ok(provider)
     }
 
     ///
@@ -276,80 +747,307 @@ impl ServiceCollection {
     ///
     /// Unlike shared services, this service's reference counter isn't checked to equal zero when the provider is dropped
     ///
+    /// Tuples are anticipated as a single service, e.g. `build_factory::<(A, B)>()` only resolves `Registered<(A, B)>`.
+    /// Splitting them would require to distinguish tuples from other `Clone` types, which isn't possible without
+    /// specialization. Use `build_tuple_factory()` to resolve `Registered<A>` and `Registered<B>` individually
+    ///
     pub fn build_factory<T: Clone + Any + Send + Sync>(
         self,
     ) -> Result<ServiceProviderFactory<T>, BuildError> {
         ServiceProviderFactory::create(self, Vec::new())
     }
 
+    /// Same as `build_factory()`, but each element of the tuple T is resolvable individually
+    /// (`build_factory::<(A, B)>()` resolves the tuple itself):
+    /// ```
+    /// use {ioc_rs::{Registered, ServiceCollection}};
+    ///
+    /// #[derive(Clone)]
+    /// struct HttpRequest(&'static str);
+    /// #[derive(Clone)]
+    /// struct UserClaims(&'static str);
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection
+    ///     .with::<(Registered<HttpRequest>, Registered<UserClaims>)>()
+    ///     .register(|(request, user)| format!("{} requested {}", user.0, request.0));
+    ///
+    /// let factory = collection.build_tuple_factory().expect("Configuration is valid");
+    /// let provider = factory.build((HttpRequest("/index.html"), UserClaims("admin")));
+    /// assert_eq!(Some("admin requested /index.html".to_string()), provider.get::<String>());
+    /// ```
+    pub fn build_tuple_factory<T: BaseValues>(
+        self,
+    ) -> Result<ServiceProviderFactory<T>, BuildError> {
+        ServiceProviderFactory::create_with_base(self, Vec::new(), T::producers(), |base| {
+            Box::new(base)
+        })
+    }
+
+    /// Same as `build_factory()` for bases which aren't `Clone` (e.g. large request contexts or sockets).
+    /// The base is moved into an `Arc` once per ServiceProvider and is resolvable as `Arc<T>`:
+    /// ```
+    /// use {ioc_rs::{Registered, ServiceCollection}, std::sync::Arc};
+    ///
+    /// struct RequestContext { path: String }
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection
+    ///     .with::<Registered<Arc<RequestContext>>>()
+    ///     .register(|context| context.path.len());
+    ///
+    /// let factory = collection.build_shared_factory().expect("Configuration is valid");
+    /// let provider = factory.build(RequestContext { path: "/index.html".into() });
+    /// assert_eq!(Some(11), provider.get::<usize>());
+    /// ```
+    /// Like with `build_factory()`, the reference counter of the base isn't checked when the provider is dropped
+    pub fn build_shared_factory<T: Any + Send + Sync>(
+        self,
+    ) -> Result<ServiceProviderFactory<T>, BuildError> {
+        ServiceProviderFactory::create_shared(self, Vec::new())
+    }
+
+    /// Returns all registered services and their dependencies. Unlike `build()`, missing and cyclic dependencies
+    /// are part of the graph instead of beeing reported as BuildError
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::new(
+            self.producer_factories
+                .iter()
+                .map(|p| (p.type_id, &p.metadata)),
+            &[],
+        )
+    }
+
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn with_parent(
         self,
         provider: impl Into<WeakServiceProvider>,
     ) -> ServiceProviderFactoryBuilder {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         ServiceProviderFactoryBuilder::create(self, provider.into())
     }
 
+    /// Services of the `parents` are looked up in their own ServiceProviders, so only the services
+    /// of this collection are validated. Stops at the first error unless `report_all` is set
     fn validate_producers(
         self,
-        mut factories: Vec<ServiceProducer>,
-    ) -> Result<(Vec<UntypedFn>, Vec<TypeId>, usize), BuildError> {
+        parents: Vec<WeakServiceProvider>,
+        report_all: bool,
+    ) -> Result<(ServiceProviderImmutableState, usize), BuildErrors> {
         let mut state_counter: usize = 0;
-        factories.extend(self.producer_factories.into_iter());
-
-        factories.sort_by_key(|a| a.type_id);
-
-        let mut final_ordered_types: Vec<TypeId> = factories.iter().map(|f| f.type_id).collect();
+        let requirements = self.requirements;
+
+        let mut factories: Vec<_> = self.producer_factories.into_iter().enumerate().collect();
+        factories.sort_by_key(|(_, a)| a.type_id);
+        let registration_order: Vec<usize> = factories.iter().map(|(i, _)| *i).collect();
+
+        let final_ordered_types: Vec<TypeId> = factories.iter().map(|(_, f)| f.type_id).collect();
+        let service_names: Vec<&'static str> = factories
+            .iter()
+            .map(|(_, f)| f.metadata.type_name)
+            .collect();
+        let service_locations: Vec<_> = factories
+            .iter()
+            .map(|(_, f)| f.metadata.registered_at)
+            .collect();
+        let mut sealed_types: BTreeMap<TypeId, &'static str> = parents
+            .iter()
+            .flat_map(|parent| parent.0.immutable_state.sealed_types.iter())
+            .map(|(id, name)| (*id, *name))
+            .collect();
 
         let mut cyclic_reference_candidates = BTreeMap::new();
         let mut producers = Vec::with_capacity(factories.len());
         let mut types = Vec::with_capacity(factories.len());
-
-        for (i, x) in factories.into_iter().enumerate() {
+        let mut metadata = Vec::with_capacity(factories.len());
+        let mut dispose_hooks = Vec::new();
+        let mut shared_registered_at = Vec::new();
+        // Errors are collected with the registration order of their services, so they don't depend on TypeIds
+        let mut missing_errors = Vec::new();
+        let mut cycle_errors = Vec::new();
+
+        for (i, (registered, mut x)) in factories.into_iter().enumerate() {
             let mut ctx = UntypedFnFactoryContext {
                 state_counter: &mut state_counter,
-                final_ordered_types: &mut final_ordered_types,
+                final_ordered_types: ServiceTypes {
+                    ids: &final_ordered_types,
+                    parents: &parents,
+                },
                 cyclic_reference_candidates: &mut cyclic_reference_candidates,
                 service_descriptor_pos: i,
+                dependencies: Vec::new(),
+                dispose_hook: x.dispose_hook.take(),
+                dispose_hooks: &mut dispose_hooks,
+                registered_at: x.metadata.registered_at,
+                shared_registered_at: &mut shared_registered_at,
+                reserved_state: None,
+            };
+            let producer = match (x.factory)(&mut ctx) {
+                Ok(producer) => producer,
+                Err(error) if !report_all => {
+                    let service = x.metadata.type_name;
+                    return Err(BuildErrors {
+                        errors: alloc::vec![ServiceBuildError {
+                            service,
+                            error: error.requested_by(&x.metadata),
+                        }],
+                    });
+                }
+                Err(error) => {
+                    let types = ServiceTypes {
+                        ids: &final_ordered_types,
+                        parents: &parents,
+                    };
+                    let metadata = &x.metadata;
+                    let missing = missing_dependencies(metadata, &types);
+                    let service = metadata.type_name;
+                    if missing.is_empty() {
+                        missing_errors.push((registered, ServiceBuildError { service, error }));
+                    }
+                    missing_errors.extend(missing.into_iter().map(|error| {
+                        let error = error.requested_by(metadata);
+                        (registered, ServiceBuildError { service, error })
+                    }));
+                    continue;
+                }
             };
-            let producer = (x.factory)(&mut ctx)?;
             debug_assert_eq!(&x.type_id, producer.get_result_type_id());
+            x.metadata.dependencies = ctx.dependencies;
+            x.metadata.shared_state = ctx.reserved_state;
+            if x.metadata.sealing == Sealing::Sealed {
+                sealed_types.insert(x.type_id, x.metadata.type_name);
+            }
             producers.push(producer);
             types.push(x.type_id);
+            metadata.push(x.metadata);
         }
 
-        CycleChecker(&mut cyclic_reference_candidates)
-            .ok()
-            .map_err(|indices| {
-                BuildError::CyclicDependency(
-                    indices
-                        .into_iter()
-                        .skip(1)
-                        .map(|i| {
-                            cyclic_reference_candidates
-                                .get(&i)
-                                .unwrap()
-                                .type_description
-                        })
-                        .fold(
-                            cyclic_reference_candidates
-                                .values()
-                                .next()
-                                .unwrap()
-                                .type_description
-                                .to_string(),
-                            |acc, n| acc + " -> " + n,
-                        ),
-                )
-            })?;
+        let service_types = ServiceTypes {
+            ids: &final_ordered_types,
+            parents: &parents,
+        };
+        for requirement in requirements {
+            if service_types.positions(requirement.type_id).count() < requirement.min_count {
+                let error = ServiceBuildError {
+                    service: requirement.missing.requested_by,
+                    error: BuildError::MissingDependency(requirement.missing),
+                };
+                if !report_all {
+                    return Err(BuildErrors {
+                        errors: alloc::vec![error],
+                    });
+                }
+                // Requirements are reported after the missing dependencies of all services
+                missing_errors.push((usize::MAX, error));
+            }
+        }
+
+        // Services of a found cycle are removed before searching the next one, so every cycle is reported once
+        let mut candidates = cyclic_reference_candidates;
+        loop {
+            let mut values = candidates
+                .iter()
+                .map(|(pos, candidate)| {
+                    (
+                        *pos,
+                        CycleCheckerValue {
+                            is_visited: false,
+                            iter: candidate.dependencies.clone().into_iter(),
+                        },
+                    )
+                })
+                .collect();
+            let mut stack = match CycleChecker(&mut values).ok() {
+                Ok(()) => break,
+                Err(stack) => stack,
+            };
+            // Only the end of the stack forms the cycle. It starts with the service which was registered last
+            // and thus closed the cycle, so the message doesn't depend on the order of TypeIds
+            let closing = stack.pop().expect("A cycle contains at least one service");
+            let start = stack.iter().position(|i| *i == closing).unwrap_or(0);
+            let mut cycle = stack.split_off(start);
+            let first = (0..cycle.len())
+                .max_by_key(|i| registration_order[cycle[*i]])
+                .unwrap_or(0);
+            cycle.rotate_left(first);
+            // Each dependency is paired with the registration of the next service, which it resolves to
+            let len = cycle.len();
+            cycle_errors.push((
+                registration_order[cycle[0]],
+                ServiceBuildError {
+                    service: service_names[cycle[0]],
+                    error: BuildError::CyclicDependency(
+                        (0..=len)
+                            .map(|k| {
+                                (
+                                    candidates[&cycle[k % len]].type_description,
+                                    service_locations[cycle[(k + 1) % len]],
+                                )
+                            })
+                            .collect(),
+                    ),
+                },
+            ));
+            if !report_all {
+                break;
+            }
+            for pos in &cycle {
+                candidates.remove(pos);
+            }
+        }
+        if !missing_errors.is_empty() || !cycle_errors.is_empty() {
+            missing_errors.sort_by_key(|(registered, _)| *registered);
+            cycle_errors.sort_by_key(|(registered, _)| *registered);
+            let errors = missing_errors
+                .into_iter()
+                .chain(cycle_errors)
+                .map(|(_, error)| error)
+                .collect();
+            return Err(BuildErrors { errors });
+        }
 
-        Ok((producers, types, state_counter))
+        let immutable_state = ServiceProviderImmutableState {
+            producers,
+            types,
+            registration_order,
+            metadata,
+            dispose_hooks,
+            shared_registered_at,
+            parents,
+            sealed_types,
+            #[cfg(feature = "std")]
+            pool: None,
+        };
+        Ok((immutable_state, state_counter))
     }
 }
 
+/// Returns all dependencies of a service, which aren't registered in the same order as `Resolvable::precheck()`
+/// would report them
+fn missing_dependencies(metadata: &ServiceMetadata, types: &ServiceTypes) -> Vec<BuildError> {
+    let mut missing = Vec::new();
+    (metadata.declared_dependencies)(&mut |dependency| {
+        let position = match dependency.kind {
+            DependencyKind::Registered => types.last(dependency.type_id),
+            DependencyKind::Inherited => types.last_inherited(dependency.type_id),
+            DependencyKind::AllRegistered | DependencyKind::ServiceProvider => return,
+        };
+        if position.is_none() {
+            missing.push(BuildError::MissingDependency(dependency.missing));
+        }
+    });
+    missing
+}
+
+struct CycleCandidate {
+    type_description: &'static str,
+    dependencies: Vec<usize>,
+}
+
 struct CycleCheckerValue {
     is_visited: bool,
-    type_description: &'static str,
-    iter: Box<dyn Iterator<Item = usize>>,
+    iter: alloc::vec::IntoIter<usize>,
 }
 struct CycleChecker<'a>(&'a mut BTreeMap<usize, CycleCheckerValue>);
 
@@ -390,13 +1088,146 @@ impl<'a> CycleChecker<'a> {
 #[derive(Debug, PartialEq, Eq)]
 pub enum BuildError {
     MissingDependency(MissingDependencyType),
-    CyclicDependency(String),
+    /// Contains the dependencies forming the cycle, where the first one is repeated at the end, each with the
+    /// registration of the service it resolves to. Displayed as `A -> B -> A (registered at a.rs:1:1, b.rs:2:1)`
+    CyclicDependency(Vec<(&'static str, Option<&'static Location<'static>>)>),
+    /// A shared service panicked while it was instantiated by `build_eager()` or because it was marked with `eager()`.
+    /// Contains the type of the failing service and the panic message
+    FailedInitialization(String),
+    /// A child registered a service, which was sealed by its parent. Contains the type of the sealed service
+    OverrideOfSealedService(String),
 }
 
+impl BuildError {
+    fn requested_by(self, service: &ServiceMetadata) -> Self {
+        match self {
+            BuildError::MissingDependency(missing) => {
+                BuildError::MissingDependency(MissingDependencyType {
+                    requested_by: service.type_name,
+                    registered_at: service.registered_at,
+                    ..missing
+                })
+            }
+            error => error,
+        }
+    }
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuildError::MissingDependency(missing) => {
+                write!(
+                    f,
+                    "Missing dependency {} of {}",
+                    missing.name, missing.requested_by
+                )?;
+                if let Some(location) = missing.registered_at {
+                    write!(f, " registered at {}", location)?;
+                }
+                Ok(())
+            }
+            BuildError::CyclicDependency(cycle) => {
+                for (i, (name, _)) in cycle.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { " -> " };
+                    write!(f, "{}{}", separator, name)?;
+                }
+                // The last entry repeats the first one
+                let services = &cycle[..cycle.len().saturating_sub(1)];
+                if services.iter().any(|(_, location)| location.is_some()) {
+                    for (i, (_, location)) in services.iter().enumerate() {
+                        f.write_str(if i == 0 { " (registered at " } else { ", " })?;
+                        match location {
+                            Some(location) => write!(f, "{}", location)?,
+                            None => f.write_str("unknown")?,
+                        }
+                    }
+                    f.write_str(")")?;
+                }
+                Ok(())
+            }
+            BuildError::FailedInitialization(msg) => write!(f, "Failed initialization of {}", msg),
+            BuildError::OverrideOfSealedService(name) => {
+                write!(f, "Override of sealed service {}", name)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// Errors which can only be detected while resolving services.
+/// `ServiceProvider::get()` panics with these errors, as they indicate a misconfiguration which couldn't be checked by
+/// `ServiceCollection::build()`
+#[non_exhaustive]
 #[derive(Debug, PartialEq, Eq)]
+pub enum ResolveError {
+    /// A shared service requested itself while beeing initialized (e.g. via `WeakServiceProvider`).
+    /// Contains the services in initialization order, e.g. `A -> B -> A`.
+    /// Detection requires the `std` feature, as the services in progress are tracked per thread
+    RecursiveResolution(String),
+}
+
+impl core::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ResolveError::RecursiveResolution(chain) => {
+                write!(f, "Recursive resolution of {}", chain)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ResolveError {}
+
+/// All errors returned by `ServiceCollection::build_all_errors()`
+#[derive(Debug, PartialEq, Eq)]
+pub struct BuildErrors {
+    /// Missing dependencies followed by the cycles, both in the registration order of their services
+    pub errors: Vec<ServiceBuildError>,
+}
+
+impl BuildErrors {
+    fn into_first(self) -> BuildError {
+        self.errors
+            .into_iter()
+            .next()
+            .expect("BuildErrors contain at least one error")
+            .error
+    }
+}
+
+/// Displays one error per line, prefixed by its service
+impl core::fmt::Display for BuildErrors {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}: {}", error.service, error.error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildErrors {}
+
+/// A BuildError and the service which caused it. For cycles, it's the service which was registered last
+#[derive(Debug, PartialEq, Eq)]
+pub struct ServiceBuildError {
+    pub service: &'static str,
+    pub error: BuildError,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct MissingDependencyType {
     id: TypeId,
     name: &'static str,
+    requested_by: &'static str,
+    registered_at: Option<&'static Location<'static>>,
 }
 
 impl MissingDependencyType {
@@ -404,8 +1235,31 @@ impl MissingDependencyType {
         Self {
             name: type_name::<T>(),
             id: TypeId::of::<T>(),
+            requested_by: "",
+            registered_at: None,
         }
     }
+
+    /// Type name of the Resolvable, e.g. `ioc_rs::Registered<i32>`
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// TypeId of the Resolvable, e.g. `TypeId::of::<Registered<i32>>()`
+    pub fn type_id(&self) -> TypeId {
+        self.id
+    }
+
+    /// Type name of the service, which depends on the missing type.
+    /// `ioc_rs::ServiceCollection` for types passed to `ServiceCollection::require()`
+    pub fn requested_by(&self) -> &'static str {
+        self.requested_by
+    }
+
+    /// Call to `register*()`, which registered the requesting service, or to `require*()`
+    pub fn registered_at(&self) -> Option<&'static Location<'static>> {
+        self.registered_at
+    }
 }
 
 pub struct ServiceBuilder<'col, T: Resolvable>(
@@ -415,49 +1269,61 @@ pub struct ServiceBuilder<'col, T: Resolvable>(
 
 
 impl<'col, TDep: Resolvable> ServiceBuilder<'col, TDep> {
-    pub fn register<'a, T: core::any::Any>(&'a mut self, creator: fn(TDep::ItemPreChecked) -> T) -> AliasBuilder<'a, T> {
+    #[track_caller]
+    pub fn register<'a, T: core::any::Any>(
+        &'a mut self,
+        creator: fn(TDep::ItemPreChecked) -> T,
+    ) -> AliasBuilder<'a, T> {
         let factory: UntypedFnFactory = Box::new(move |ctx| {
-            let key = TDep::precheck(ctx.final_ordered_types)?;
+            let key = TDep::precheck(&ctx.final_ordered_types)?;
             ctx.register_cyclic_reference_candidate(
                 core::any::type_name::<TDep::ItemPreChecked>(),
-                Box::new(TDep::iter_positions(ctx.final_ordered_types)),
+                Box::new(TDep::iter_positions(&ctx.final_ordered_types)),
             );
             let func: Box<dyn Fn(&ServiceProvider) -> T> =
                 Box::new(move |provider: &ServiceProvider| {
+                    #[cfg(feature = "track-leaks")]
+                    let _frame = leak_tracking::enter_service(type_name::<T>());
+                    #[cfg(feature = "std")]
+                    let _guard =
+                        resolution_stack::ResolutionGuard::enter_transient(type_name::<T>());
                     let arg = TDep::resolve_prechecked(provider, &key);
                     creator(arg)
                 });
             Ok(func.into())
         });
-        self.0
-            .producer_factories
-            .push(ServiceProducer::new::<T>(factory));
+        self.0.push_producer(
+            ServiceProducer::new::<T>(factory, ServiceLifetime::Transient).depending_on::<TDep>(),
+        );
 
         AliasBuilder(&mut self.0, PhantomData)
     }
+    #[track_caller]
     pub fn register_shared<'a, T: core::any::Any + Send + Sync>(
         &'a mut self,
         creator: fn(TDep::ItemPreChecked) -> alloc::sync::Arc<T>,
     ) -> AliasBuilder<Arc<T>> {
         let factory: UntypedFnFactory = Box::new(move |ctx| {
             let service_state_idx = ctx.reserve_state_space();
-            let key = TDep::precheck(ctx.final_ordered_types)?;
+            let key = TDep::precheck(&ctx.final_ordered_types)?;
             ctx.register_cyclic_reference_candidate(
                 core::any::type_name::<TDep::ItemPreChecked>(),
-                Box::new(TDep::iter_positions(ctx.final_ordered_types)),
+                Box::new(TDep::iter_positions(&ctx.final_ordered_types)),
             );
             let func: Box<dyn Fn(&ServiceProvider) -> alloc::sync::Arc<T>> =
                 Box::new(move |provider: &ServiceProvider| {
                     let moved_key = &key;
                     provider.get_or_initialize_pos(service_state_idx, move || {
+                        #[cfg(feature = "track-leaks")]
+                        let _frame = leak_tracking::enter_service(type_name::<Arc<T>>());
                         creator(TDep::resolve_prechecked(provider, &moved_key))
                     })
                 });
             Ok(func.into())
         });
-        self.0
-            .producer_factories
-            .push(ServiceProducer::new::<alloc::sync::Arc<T>>(factory));
+        self.0.push_producer(
+            ServiceProducer::new_shared::<alloc::sync::Arc<T>>(factory).depending_on::<TDep>(),
+        );
 
         AliasBuilder(&mut self.0, PhantomData)
     }
@@ -466,12 +1332,21 @@ impl<'col, TDep: Resolvable> ServiceBuilder<'col, TDep> {
 /// ServiceProviders are created directly from ServiceCollections or ServiceProviderFactories and can be used
 /// to retrieve services by type. ServiceProviders are final and cannot be modified anymore. When a ServiceProvider goes
 /// out of scope, all related WeakServiceProviders and shared services have to be dropped already. Otherwise
-/// dropping the original ServiceProvider results in a call to ioc-rs::ERROR_HANDLER, which panics in std and enabled debug_assertions
+/// dropping the original ServiceProvider is handled by its `LeakPolicy` or the error handler configured with
+/// `set_error_handler()`, which panics in std and enabled debug_assertions
 pub struct ServiceProvider {
     immutable_state: Arc<ServiceProviderImmutableState>,
-    service_states: Arc<ServiceProviderMutableState>,
-    #[cfg(debug_assertions)]
+    /// Only dropped in `Drop::drop()`, which moves it into the StatePool of pooled ServiceProviders
+    service_states: ManuallyDrop<Arc<ServiceProviderMutableState>>,
     is_root: bool,
+    leak_policy: Option<LeakPolicy>,
+    /// Created by `ServiceProviderFactory::build_pooled()`, so the state is returned to the pool of the
+    /// immutable state after dropping
+    #[cfg(feature = "std")]
+    pooled: bool,
+    /// Identifies the origin of a WeakServiceProvider in ServiceProviderMutableState::origins
+    #[cfg(feature = "track-leaks")]
+    origin_id: Option<usize>,
 }
 
 impl Debug for ServiceProvider {
@@ -487,51 +1362,69 @@ impl Debug for ServiceProvider {
 /// Dropping ServiceProviders created by ServiceCollection::build() or ServiceProviderFactory::build()
 /// directly are expected to have no remaining clones when they are dropped. Clones could be used in services
 /// which have a dependency to ServiceProvider or ServiceIterators<T>, which are using ServiceProvider internally)
-#[cfg(debug_assertions)]
-#[allow(clippy::needless_collect)]
+///
+/// Shared services are disposed by whichever ServiceProvider or WeakServiceProvider is dropped last
 impl Drop for ServiceProvider {
     fn drop(&mut self) {
-        if !self.is_root {
-            return;
+        #[cfg(feature = "track-leaks")]
+        if let Some(id) = self.origin_id {
+            self.service_states.origins().remove_provider(id);
         }
-
-        let mut swapped_service_states = Arc::new(ServiceProviderMutableState {
-            base: None,
-            shared_services: Vec::new(),
-        });
-        core::mem::swap(&mut swapped_service_states, &mut self.service_states);
-
-        match Arc::try_unwrap(swapped_service_states) {
-            Ok(service_states) => {
-                let checkers: Vec<_> = service_states
-                    .shared_services
-                    .into_iter()
-                    .filter_map(|c| c.get().and_then(|x| x.get_weak_checker_if_dangling()))
-                    .collect();
-                let errors: Vec<_> = checkers
-                    .into_iter()
-                    .filter_map(|c| {
-                        let v = (c)();
-                        (v.remaining_references > 0).then(|| v)
-                    })
-                    .collect();
-
-                if !errors.is_empty() {
-                    unsafe {
-                        ERROR_HANDLER(&alloc::format!(
-                            "Some instances outlived their ServiceProvider: {:?}",
-                            errors
-                        ))
-                    };
+        let check_leaks = self.is_root && (self.leak_policy.is_some() || cfg!(debug_assertions));
+        let released = self.release(check_leaks);
+        // Never accessed again, as the ServiceProvider is dropped
+        let service_states = unsafe { ManuallyDrop::take(&mut self.service_states) };
+        match released {
+            Err(report) => match &self.leak_policy {
+                Some(policy) => policy.handle(&report),
+                None => leak_policy::handle_error(&report),
+            },
+            #[cfg(feature = "std")]
+            Ok(()) => {
+                if let (true, Some(pool)) = (self.pooled, &self.immutable_state.pool) {
+                    pool.recycle(service_states);
                 }
             }
-            Err(x) => unsafe {
-                ERROR_HANDLER(&alloc::format!(
-                    "Original ServiceProvider was dropped while still beeing used {} times",
-                    Arc::strong_count(&x) - 1
-                ));
-            },
+            #[cfg(not(feature = "std"))]
+            Ok(()) => drop(service_states),
+        }
+    }
+}
+
+/// Lists everything which outlived its ServiceProvider. Returned by `ServiceProvider::close()`
+/// and passed to the `LeakPolicy` or error handler when a ServiceProvider is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LeakReport {
+    /// Number of WeakServiceProviders and ServiceIterators still referencing the ServiceProvider.
+    /// Shared services are only checked, if there are none, as they cannot be disposed otherwise
+    pub remaining_providers: usize,
+    /// Shared services, which are still referenced after they were disposed by the ServiceProvider
+    pub services: Vec<LeakedService>,
+    /// Where the remaining providers or all references to the leaked services were obtained
+    #[cfg(feature = "track-leaks")]
+    pub origins: Vec<LeakOrigin>,
+}
+
+impl Debug for LeakReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.remaining_providers > 0 {
+            write!(
+                f,
+                "Original ServiceProvider was dropped while still beeing used {} times",
+                self.remaining_providers
+            )?;
+        } else {
+            write!(
+                f,
+                "Some instances outlived their ServiceProvider: {:?}",
+                self.services
+            )?;
+        }
+        #[cfg(feature = "track-leaks")]
+        if !self.origins.is_empty() {
+            write!(f, ", obtained as {:?}", self.origins)?;
         }
+        Ok(())
     }
 }
 
@@ -540,36 +1433,191 @@ impl ServiceProvider {
         T::resolve(self)
     }
 
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn resolve_unchecked<T: Resolvable>(&self) -> T::ItemPreChecked {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         let precheck_key =
-            T::precheck(&self.immutable_state.types).expect("Resolve unkwnown service");
+            T::precheck(&self.immutable_state.service_types()).expect("Resolve unkwnown service");
         T::resolve_prechecked(self, &precheck_key)
     }
 
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn get<T: Any>(&self) -> Option<T> {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         self.resolve::<Registered<T>>()
     }
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn get_all<T: Any>(&self) -> ServiceIterator<Registered<T>> {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         self.resolve::<AllRegistered<T>>()
     }
 
+    /// Lists the registrations of this ServiceProvider and its parents. See `ServiceManifest` for more details
+    pub fn describe(&self) -> ServiceManifest {
+        ServiceManifest::new(self)
+    }
+
+    /// Returns the own services in registration order followed by the base of ServiceProviderFactories,
+    /// their dependencies and the services of parents they depend on
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let state = &self.immutable_state;
+        let mut positions: Vec<usize> = (0..state.types.len()).collect();
+        positions.sort_by_key(|pos| state.registration_order[*pos]);
+        DependencyGraph::new(
+            positions
+                .into_iter()
+                .map(|pos| (state.types[pos], &state.metadata[pos])),
+            &state.parents,
+        )
+    }
+
+    /// Creates a WeakServiceProvider sharing the state of this ServiceProvider.
+    /// `holder` describes the type which keeps it alive for LeakReports
+    #[cfg_attr(not(feature = "track-leaks"), allow(unused_variables))]
+    fn weak(&self, holder: &'static str) -> WeakServiceProvider {
+        WeakServiceProvider(ServiceProvider {
+            immutable_state: self.immutable_state.clone(),
+            service_states: self.service_states.clone(),
+            is_root: false,
+            leak_policy: None,
+            #[cfg(feature = "std")]
+            pooled: false,
+            #[cfg(feature = "track-leaks")]
+            origin_id: Some(
+                self.service_states
+                    .origins()
+                    .add_provider(leak_tracking::current_origin(holder)),
+            ),
+        })
+    }
+
+    /// Disposes all shared services in reverse creation order, calling their hooks registered with `on_dispose()`.
+    /// Unlike dropping the ServiceProvider, leaking shared services, WeakServiceProviders and ServiceIterators
+    /// are checked in all build profiles and returned instead of beeing passed to the `LeakPolicy` or error handler:
+    /// ```
+    /// use {ioc_rs::ServiceCollection, std::sync::Arc};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection.register_shared(|| Arc::new(42));
+    /// let provider = collection.build().expect("Configuration is valid");
+    /// let leaking = provider.get::<Arc<i32>>();
+    ///
+    /// let report = provider.close().expect_err("Arc<i32> is still referenced");
+    /// assert_eq!("i32", report.services[0].typename);
+    /// assert_eq!(1, report.services[0].remaining_references);
+    /// ```
+    pub fn close(mut self) -> Result<(), LeakReport> {
+        // Drop must not report the same leaks again
+        self.is_root = false;
+        self.release(true)
+    }
+
+    /// Disposes the shared services, if no other WeakServiceProvider references them anymore.
+    /// With `check_leaks`, remaining references to the disposed services or to this provider are reported
+    #[allow(clippy::needless_collect)]
+    fn release(&mut self, check_leaks: bool) -> Result<(), LeakReport> {
+        let service_states = match Arc::get_mut(&mut *self.service_states) {
+            Some(service_states) => service_states,
+            None if check_leaks => {
+                return Err(LeakReport {
+                    remaining_providers: Arc::strong_count(&self.service_states) - 1,
+                    services: Vec::new(),
+                    #[cfg(feature = "track-leaks")]
+                    origins: self.service_states.origins().providers().cloned().collect(),
+                })
+            }
+            None => return Ok(()),
+        };
+
+        let checkers: Vec<_> = if check_leaks {
+            service_states
+                .shared_services
+                .iter()
+                .enumerate()
+                .filter_map(|(pos, c)| {
+                    c.get()
+                        .and_then(|x| x.pointer.get_weak_checker_if_dangling())
+                        .map(|checker| (pos, checker))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        service_states.dispose(&self.immutable_state.dispose_hooks);
+
+        let shared_registered_at = &self.immutable_state.shared_registered_at;
+        let (_leaked_positions, services): (Vec<_>, Vec<_>) = checkers
+            .into_iter()
+            .filter_map(|(pos, c)| {
+                let v = LeakedService {
+                    registered_at: shared_registered_at[pos],
+                    ..(c)()
+                };
+                (v.remaining_references > 0).then_some((pos, v))
+            })
+            .unzip();
+
+        if services.is_empty() {
+            Ok(())
+        } else {
+            Err(LeakReport {
+                remaining_providers: 0,
+                services,
+                #[cfg(feature = "track-leaks")]
+                origins: {
+                    let origins = service_states.origins();
+                    _leaked_positions
+                        .into_iter()
+                        .flat_map(|pos| origins.services(pos).iter().cloned())
+                        .collect()
+                },
+            })
+        }
+    }
+
     fn get_or_initialize_pos<T: Any + Send + Sync, TFn: Fn() -> Arc<T>>(
         &self,
         index: usize,
         initializer: TFn,
     ) -> Arc<T> {
-        let pointer = self
-            .service_states
-            .shared_services
-            .get(index)
-            .unwrap()
-            .get_or_init(|| UntypedPointer::new(initializer()));
-        unsafe { pointer.clone_as::<Arc<T>>() }
+        let cell = self.service_states.shared_services.get(index).unwrap();
+        let pointer = match cell.get() {
+            Some(pointer) => pointer,
+            None => {
+                #[cfg(feature = "std")]
+                let _guard = resolution_stack::ResolutionGuard::enter(
+                    cell as *const _ as usize,
+                    type_name::<Arc<T>>(),
+                )
+                .unwrap_or_else(|e| panic!("{}", e));
+                cell.get_or_init(|| {
+                    let pointer = UntypedPointer::new(initializer());
+                    // Assigned after initialization, so dependencies always have a lower index
+                    let creation_index = self
+                        .service_states
+                        .creation_counter
+                        .fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+                    SharedService {
+                        pointer,
+                        creation_index,
+                    }
+                })
+            }
+        };
+        #[cfg(feature = "track-leaks")]
+        self.service_states
+            .origins()
+            .add_service(index, leak_tracking::current_origin(type_name::<T>()));
+        unsafe { pointer.pointer.clone_as::<Arc<T>>() }
     }
 }
 
 /// Weak ServiceProviders have the same public API as ServiceProviders, but cannot outlive
-/// their original ServiceProvider. If they do, the iocrs::ERROR_HANDLER is called
+/// their original ServiceProvider. If they do, the `LeakPolicy` or error handler of the original ServiceProvider is called
 pub struct WeakServiceProvider(ServiceProvider);
 
 impl WeakServiceProvider {
@@ -577,53 +1625,147 @@ impl WeakServiceProvider {
         T::resolve(&self.0)
     }
 
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn resolve_unchecked<T: Resolvable>(&self) -> T::ItemPreChecked {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         let precheck_key =
-            T::precheck(&self.0.immutable_state.types).expect("Resolve unkwnown service");
+            T::precheck(&self.0.immutable_state.service_types()).expect("Resolve unkwnown service");
         T::resolve_prechecked(&self.0, &precheck_key)
     }
 
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn get<T: Any>(&self) -> Option<T> {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         self.resolve::<Registered<T>>()
     }
 
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     pub fn get_all<T: Any>(&self) -> ServiceIterator<Registered<T>> {
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
         self.resolve::<AllRegistered<T>>()
     }
 }
 
 impl Clone for WeakServiceProvider {
+    #[cfg_attr(feature = "track-leaks", track_caller)]
     fn clone(&self) -> Self {
-        Self(ServiceProvider {
-            immutable_state: self.0.immutable_state.clone(),
-            service_states: self.0.service_states.clone(),
-            #[cfg(debug_assertions)]
-            is_root: false,
-        })
+        #[cfg(feature = "track-leaks")]
+        let _frame = leak_tracking::enter_call(core::panic::Location::caller());
+        self.0.weak(type_name::<WeakServiceProvider>())
     }
 }
 
 impl<'a> From<&'a ServiceProvider> for WeakServiceProvider {
     fn from(provider: &'a ServiceProvider) -> Self {
-        WeakServiceProvider(ServiceProvider {
-            immutable_state: provider.immutable_state.clone(),
-            service_states: provider.service_states.clone(),
-            #[cfg(debug_assertions)]
-            is_root: false,
-        })
+        provider.weak(type_name::<WeakServiceProvider>())
     }
 }
 
 struct ServiceProviderImmutableState {
+    /// Types of the own services, excluding the services of the parents
     types: Vec<TypeId>,
+    /// Indexed like `types`, the position of each service in the order of its registration
+    registration_order: Vec<usize>,
     producers: Vec<UntypedFn>,
-    // Unsafe-Code, which generates UntypedFn from parent, relies on the fact that parent ServiceProvider outlives this state
-    _parents: Vec<WeakServiceProvider>,
+    metadata: Vec<ServiceMetadata>,
+    /// Indexed by the position of the shared service state
+    dispose_hooks: Vec<Option<DisposeHook>>,
+    /// Indexed by the position of the shared service state
+    shared_registered_at: Vec<Option<&'static Location<'static>>>,
+    // Unsafe-Code relies on the fact that ServicePosition::Inherited points into the parents, which are never
+    // modified and outlive this state
+    parents: Vec<WeakServiceProvider>,
+    /// Services sealed by this ServiceProvider or its ancestors, which children must not register
+    sealed_types: BTreeMap<TypeId, &'static str>,
+    /// States recycled by ServiceProviders created with `ServiceProviderFactory::build_pooled()`
+    #[cfg(feature = "std")]
+    pool: Option<state_pool::StatePool>,
+}
+
+impl ServiceProviderImmutableState {
+    fn service_types(&self) -> ServiceTypes<'_> {
+        ServiceTypes {
+            ids: &self.types,
+            parents: &self.parents,
+        }
+    }
 }
 
 struct ServiceProviderMutableState {
     base: Option<Box<dyn Any + Send + Sync>>,
-    shared_services: Vec<OnceCell<UntypedPointer>>,
+    shared_services: Vec<OnceCell<SharedService>>,
+    creation_counter: core::sync::atomic::AtomicUsize,
+    /// Buffer of `dispose()`, which is kept when the state is recycled by a StatePool
+    dispose_order: Vec<(usize, usize)>,
+    #[cfg(feature = "track-leaks")]
+    origins: std::sync::Mutex<leak_tracking::LeakOrigins>,
+}
+
+#[cfg(feature = "track-leaks")]
+impl ServiceProviderMutableState {
+    fn origins(&self) -> std::sync::MutexGuard<'_, leak_tracking::LeakOrigins> {
+        // Origins are never modified while panicking, so the data is consistent even if the Mutex is poisoned
+        self.origins
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+struct SharedService {
+    pointer: UntypedPointer,
+    creation_index: usize,
+}
+
+impl ServiceProviderMutableState {
+    fn new(shared_services_count: usize, base: Option<Box<dyn Any + Send + Sync>>) -> Self {
+        Self {
+            base,
+            shared_services: core::iter::repeat_with(OnceCell::new)
+                .take(shared_services_count)
+                .collect(),
+            creation_counter: core::sync::atomic::AtomicUsize::new(0),
+            dispose_order: Vec::new(),
+            #[cfg(feature = "track-leaks")]
+            origins: Default::default(),
+        }
+    }
+
+    /// Prepares a state, whose shared services were disposed already, for the next ServiceProvider
+    #[cfg(feature = "std")]
+    fn reset(&mut self) {
+        debug_assert!(self.shared_services.iter().all(|s| s.get().is_none()));
+        self.base = None;
+        *self.creation_counter.get_mut() = 0;
+        #[cfg(feature = "track-leaks")]
+        {
+            *self.origins() = Default::default();
+        }
+    }
+
+    /// Drops all shared services in reverse creation order. The dispose hook of a service is called right before it's dropped.
+    fn dispose(&mut self, dispose_hooks: &[Option<DisposeHook>]) {
+        let mut created = core::mem::take(&mut self.dispose_order);
+        created.extend(
+            self.shared_services
+                .iter()
+                .enumerate()
+                .filter_map(|(pos, c)| c.get().map(|s| (s.creation_index, pos))),
+        );
+        created.sort_unstable_by(|a, b| b.cmp(a));
+
+        for &(_, pos) in &created {
+            if let Some(service) = self.shared_services[pos].take() {
+                if let Some(hook) = &dispose_hooks[pos] {
+                    hook(&service.pointer);
+                }
+            }
+        }
+        created.clear();
+        self.dispose_order = created;
+    }
 }
 
 #[cfg(test)]
@@ -665,7 +1807,7 @@ mod tests {
     #[test]
     #[cfg(debug_assertions)]
     #[should_panic(
-        expected = "Some instances outlived their ServiceProvider: [Type: i32 (remaining 1)]"
+        expected = "Some instances outlived their ServiceProvider: [Type: i32 (remaining 1) registered at "
     )]
     fn drop_service_provider_with_existing_shared_registered_is_panicking() {
         let mut _outer = None;
@@ -677,6 +1819,162 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(
+        expected = "Recursive resolution of alloc::sync::Arc<i32> -> alloc::sync::Arc<i64> -> alloc::sync::Arc<i32>"
+    )]
+    fn shared_service_resolving_itself_through_weak_service_provider_is_detected() {
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<WeakServiceProvider>()
+            .register_shared(|p| Arc::new(*p.get::<Arc<i64>>().unwrap() as i32));
+        collection
+            .with::<WeakServiceProvider>()
+            .register_shared(|p| Arc::new(*p.get::<Arc<i32>>().unwrap() as i64));
+        let provider = collection.build().unwrap();
+        provider.get::<Arc<i32>>();
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(
+        expected = "Recursive resolution of alloc::sync::Arc<i32> -> i16 -> alloc::sync::Arc<i64> -> alloc::sync::Arc<i32>"
+    )]
+    fn transient_services_are_part_of_recursive_resolution_chain() {
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<WeakServiceProvider>()
+            .register_shared(|p| Arc::new(p.get::<i16>().unwrap() as i32));
+        collection
+            .with::<WeakServiceProvider>()
+            .register(|p| *p.get::<Arc<i64>>().unwrap() as i16);
+        collection
+            .with::<WeakServiceProvider>()
+            .register_shared(|p| Arc::new(*p.get::<Arc<i32>>().unwrap() as i64));
+        let provider = collection.build().unwrap();
+        provider.get::<Arc<i32>>();
+    }
+
+    #[test]
+    fn close_reports_leaking_shared_services() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(1i32));
+        let registered_at = Location::caller();
+        let line = line!() - 2;
+        let provider = collection.build().unwrap();
+        let _leaking = provider.get::<Arc<i32>>();
+
+        let report = provider.close().unwrap_err();
+        assert_eq!(0, report.remaining_providers);
+        let location = report.services[0].registered_at.unwrap();
+        assert_eq!(
+            (registered_at.file(), line),
+            (location.file(), location.line())
+        );
+        assert_eq!(
+            vec![LeakedService {
+                typename: "i32",
+                remaining_references: 1,
+                registered_at: Some(location),
+            }],
+            report.services
+        );
+    }
+
+    #[test]
+    fn close_reports_remaining_weak_service_providers() {
+        let mut collection = ServiceCollection::new();
+        collection.with::<WeakServiceProvider>().register(|p| p);
+        collection.register(|| 1i32);
+        let provider = collection.build().unwrap();
+        let weak = provider.get::<WeakServiceProvider>().unwrap();
+        let iterator = provider.get_all::<i32>();
+
+        let report = provider.close().unwrap_err();
+        assert_eq!(2, report.remaining_providers);
+        assert!(report.services.is_empty());
+        drop((weak, iterator));
+    }
+
+    #[test]
+    fn close_without_leaks_succeeds() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(1i32));
+        let provider = collection.build().unwrap();
+        assert_eq!(Some(1), provider.get::<Arc<i32>>().map(|i| *i));
+        assert_eq!(Ok(()), provider.close());
+    }
+
+    #[test]
+    fn leak_policy_is_applied_in_all_build_profiles() {
+        static REPORTED: AtomicI32 = AtomicI32::new(0);
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(1i32));
+        collection.set_leak_policy(LeakPolicy::Callback(|report| {
+            REPORTED.fetch_add(
+                report.services[0].remaining_references as i32,
+                Ordering::SeqCst,
+            );
+        }));
+        let provider = collection.build().unwrap();
+        let _leaking = provider.get::<Arc<i32>>();
+        drop(provider);
+        assert_eq!(1, REPORTED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn leak_policy_ignore_doesnt_panic() {
+        let mut collection = ServiceCollection::new();
+        collection.with::<WeakServiceProvider>().register(|p| p);
+        collection.set_leak_policy(LeakPolicy::Ignore);
+        let provider = collection.build().unwrap();
+        let _leaking = provider.get::<WeakServiceProvider>();
+        drop(provider);
+    }
+
+    #[test]
+    #[cfg(feature = "track-leaks")]
+    fn leak_report_contains_origins_of_leaking_services() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(1i32));
+        let provider = collection.build().unwrap();
+        let _leaking = provider.get::<Arc<i32>>();
+        let line = line!() - 1;
+
+        let report = provider.close().unwrap_err();
+        assert_eq!(1, report.origins.len());
+        assert_eq!("i32", report.origins[0].typename);
+        assert_eq!(None, report.origins[0].requested_by);
+        assert_eq!(line, report.origins[0].location.unwrap().line());
+    }
+
+    #[test]
+    #[cfg(feature = "track-leaks")]
+    fn leak_report_contains_origins_of_remaining_providers() {
+        struct Holder(#[allow(dead_code)] WeakServiceProvider);
+        let mut collection = ServiceCollection::new();
+        collection.with::<WeakServiceProvider>().register(Holder);
+        collection.register(|| 1i32);
+        let provider = collection.build().unwrap();
+        let holder = provider.get::<Holder>().unwrap();
+        let line = line!() - 1;
+        let iterator = provider.get_all::<i32>();
+
+        let report = provider.close().unwrap_err();
+        assert_eq!(2, report.origins.len());
+        let weak = &report.origins[0];
+        assert_eq!("ioc_rs::WeakServiceProvider", weak.typename);
+        assert!(weak.requested_by.unwrap().ends_with("Holder"));
+        assert_eq!(line, weak.location.unwrap().line());
+        assert_eq!(
+            "ioc_rs::ServiceIterator<ioc_rs::Registered<i32>>",
+            report.origins[1].typename
+        );
+        assert_eq!(None, report.origins[1].requested_by);
+        drop((holder, iterator));
+    }
+
     #[test]
     fn resolve_last() {
         let mut col = ServiceCollection::new();
@@ -741,12 +2039,13 @@ mod tests {
     }
 
     fn build_with_missing_dependency_fails<T: Resolvable>(missing_msg_parts: &[&str]) {
-        fn check(mut col: ServiceCollection, missing_msg_parts: &[&str]) {
+        fn check(mut col: ServiceCollection, missing_msg_parts: &[&str], requested_by: &str) {
             col.register(|| 1);
             match col.build() {
                 Ok(_) => panic!("Build with missing dependency should fail"),
                 Err(e) => match e {
                     BuildError::MissingDependency(msg) => {
+                        assert_eq!(requested_by, msg.requested_by());
                         for part in missing_msg_parts {
                             assert!(
                                 msg.name.contains(part),
@@ -762,11 +2061,48 @@ mod tests {
         }
         let mut col = ServiceCollection::new();
         col.with::<T>().register(|_| ());
-        check(col, missing_msg_parts);
+        check(col, missing_msg_parts, "()");
 
         let mut col = ServiceCollection::new();
         col.with::<T>().register_shared(|_| Arc::new(()));
-        check(col, missing_msg_parts);
+        check(col, missing_msg_parts, "alloc::sync::Arc<()>");
+    }
+
+    #[test]
+    fn require_all_counts_registrations_of_parents_and_bases() {
+        fn child() -> ServiceCollection {
+            let mut child = ServiceCollection::new();
+            child.register(|| 2u8);
+            child.require_all::<u8>(3);
+            child.require::<i32>();
+            child
+        }
+        let line = line!() - 4;
+        let mut parent = ServiceCollection::new();
+        parent.register(|| 1u8);
+        let parent = parent.build().unwrap();
+
+        let errors = child().build_all_errors().map(|_| ()).unwrap_err().errors;
+        let missing: Vec<_> = errors
+            .iter()
+            .map(|e| match &e.error {
+                BuildError::MissingDependency(m) => (m.name(), m.registered_at().unwrap().line()),
+                _ => panic!("Unexpected Error"),
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("ioc_rs::AllRegistered<u8>", line),
+                ("ioc_rs::Registered<i32>", line + 1)
+            ],
+            missing
+        );
+
+        assert!(child().with_parent(&parent).build_factory::<u8>().is_err());
+        assert!(child()
+            .with_parent(&parent)
+            .build_tuple_factory::<(u8, i32)>()
+            .is_ok());
     }
 
     #[test]
@@ -1000,6 +2336,30 @@ mod tests {
         assert_eq!(1, Arc::strong_count(&inner));
     }
 
+    #[test]
+    fn shared_services_are_disposed_in_reverse_creation_order() {
+        static DISPOSED: std::sync::Mutex<Vec<&str>> = std::sync::Mutex::new(Vec::new());
+        struct Recorder(&'static str);
+        impl Drop for Recorder {
+            fn drop(&mut self) {
+                DISPOSED.lock().unwrap().push(self.0);
+            }
+        }
+
+        let mut col = ServiceCollection::new();
+        col.register_shared(|| Arc::new(Recorder("pool")));
+        col.with::<Registered<Arc<Recorder>>>()
+            .register_shared(|_| Arc::new(1i32))
+            .on_dispose(|_| DISPOSED.lock().unwrap().push("repository"));
+        col.register_shared(|| Arc::new(0u8))
+            .on_dispose(|_| panic!("Never created, so it must not be disposed"));
+        let prov = col.build().expect("Expected to have all dependencies");
+        prov.get::<Arc<i32>>();
+        prov.close().unwrap();
+
+        assert_eq!(vec!["repository", "pool"], *DISPOSED.lock().unwrap());
+    }
+
     #[test]
     fn register_instance() {
         let mut col = ServiceCollection::new();
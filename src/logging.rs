@@ -0,0 +1,50 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl core::str::FromStr for LogLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Debug" => Ok(LogLevel::Debug),
+            "Info" => Ok(LogLevel::Info),
+            "Warning" => Ok(LogLevel::Warning),
+            "Error" => Ok(LogLevel::Error),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Logger registered as `Arc<dyn Log>` by `HostBuilder`
+pub trait Log: Send + Sync {
+    fn log(&self, level: LogLevel, message: &str);
+}
+
+/// Writes all messages with at least `min_level` to stderr
+#[cfg(feature = "std")]
+pub struct ConsoleLog {
+    pub min_level: LogLevel,
+}
+
+#[cfg(feature = "std")]
+impl Log for ConsoleLog {
+    fn log(&self, level: LogLevel, message: &str) {
+        if level >= self.min_level {
+            std::eprintln!("[{:?}] {}", level, message);
+        }
+    }
+}
+
+/// Discards all messages. Used if no logger is configured without the `std` feature
+#[cfg(not(feature = "std"))]
+pub(crate) struct NoLog;
+
+#[cfg(not(feature = "std"))]
+impl Log for NoLog {
+    fn log(&self, _: LogLevel, _: &str) {}
+}
@@ -0,0 +1,153 @@
+use {
+    crate::{DependencyKind, ServiceLifetime, ServiceMetadata, ServiceProvider},
+    alloc::vec::Vec,
+};
+
+/// Registrations of a ServiceProvider and its parents returned by `ServiceProvider::describe()`.
+/// Serializable with the `serde` feature, e.g. to compare the services of two releases:
+/// ```
+/// use {ioc_rs::{DependencyKind, Registered, ServiceCollection, ServiceLifetime}};
+///
+/// let mut parent = ServiceCollection::new();
+/// parent.register(|| 42u8);
+/// let parent = parent.build().expect("Configuration is valid");
+///
+/// let mut child = ServiceCollection::new();
+/// child.with::<Registered<u8>>().register(|byte| byte as u16);
+/// let provider = child
+///     .with_parent(&parent)
+///     .build_factory::<()>()
+///     .expect("Configuration is valid")
+///     .build(());
+///
+/// let manifest = provider.describe();
+/// let registration = manifest.registrations.iter().find(|r| r.type_name == "u16").unwrap();
+/// assert_eq!(ServiceLifetime::Transient, registration.lifetime);
+/// assert_eq!("u8", registration.dependencies[0].type_name);
+/// assert_eq!(DependencyKind::Registered, registration.dependencies[0].kind);
+/// assert!(manifest.registrations.iter().any(|r| r.type_name == "u8" && r.inherited));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServiceManifest {
+    /// Registrations of the parents in the order they were added, followed by the own registrations.
+    /// Registrations of each ServiceProvider are ordered by type name, so the order doesn't change between builds.
+    /// Ancestors shared by multiple parents are only listed once
+    pub registrations: Vec<RegistrationDescriptor>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RegistrationDescriptor {
+    pub type_name: &'static str,
+    pub lifetime: ServiceLifetime,
+    /// Dependencies passed to `ServiceCollection::with()`
+    pub dependencies: Vec<DependencyDescriptor>,
+    /// Module or plugin passed to `ServiceCollection::set_origin()` before the service was registered
+    pub origin: Option<&'static str>,
+    /// Registered in a parent ServiceProvider
+    pub inherited: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DependencyDescriptor {
+    pub type_name: &'static str,
+    pub kind: DependencyKind,
+}
+
+impl ServiceManifest {
+    pub(crate) fn new(provider: &ServiceProvider) -> Self {
+        let mut registrations = Vec::new();
+        provider.visit_registrations(|_, metadata, inherited| {
+            registrations.push(RegistrationDescriptor {
+                inherited,
+                ..describe(metadata)
+            })
+        });
+        Self { registrations }
+    }
+}
+
+fn describe(metadata: &ServiceMetadata) -> RegistrationDescriptor {
+    let mut dependencies = Vec::new();
+    (metadata.declared_dependencies)(&mut |dependency| {
+        dependencies.push(DependencyDescriptor {
+            type_name: dependency.type_name,
+            kind: dependency.kind,
+        })
+    });
+    RegistrationDescriptor {
+        type_name: metadata.type_name,
+        lifetime: metadata.lifetime,
+        dependencies,
+        origin: metadata.origin,
+        inherited: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{AllRegistered, ServiceCollection, WeakServiceProvider},
+        alloc::sync::Arc,
+    };
+
+    #[test]
+    fn describe_registrations_with_origin() {
+        let mut collection = ServiceCollection::new();
+        collection.register_instance(1u8);
+        collection.set_origin("plugin");
+        collection
+            .with::<(AllRegistered<u8>, WeakServiceProvider)>()
+            .register_shared(|(bytes, _)| Arc::new(bytes.count()));
+        let provider = collection.build_factory::<i32>().unwrap().build(1);
+
+        let manifest = provider.describe();
+        let registrations: Vec<_> = manifest
+            .registrations
+            .iter()
+            .map(|r| (r.type_name, r.lifetime, r.origin))
+            .collect();
+        assert_eq!(
+            alloc::vec![
+                (
+                    "alloc::sync::Arc<usize>",
+                    ServiceLifetime::Shared,
+                    Some("plugin")
+                ),
+                ("i32", ServiceLifetime::Base, None),
+                ("u8", ServiceLifetime::Instance, None),
+            ],
+            registrations
+        );
+        assert_eq!(
+            alloc::vec![
+                DependencyDescriptor {
+                    type_name: "u8",
+                    kind: DependencyKind::AllRegistered
+                },
+                DependencyDescriptor {
+                    type_name: "ioc_rs::WeakServiceProvider",
+                    kind: DependencyKind::ServiceProvider
+                },
+            ],
+            manifest.registrations[0].dependencies
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_manifest_as_json() {
+        let mut collection = ServiceCollection::new();
+        collection.set_origin("plugin");
+        collection.register(|| 1u8);
+        let manifest = collection.build().unwrap().describe();
+
+        assert_eq!(
+            r#"{"registrations":[{"type_name":"u8","lifetime":"Transient","dependencies":[],"origin":"plugin","inherited":false}]}"#,
+            serde_json::to_string(&manifest).unwrap()
+        );
+    }
+}
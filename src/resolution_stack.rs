@@ -0,0 +1,60 @@
+use {
+    crate::ResolveError,
+    alloc::{string::String, vec::Vec},
+    std::cell::RefCell,
+};
+
+std::thread_local! {
+    /// Services which are currently resolved by this thread, outermost first. Only shared services have a key,
+    /// transient services are tracked to report the full chain
+    static IN_PROGRESS: RefCell<Vec<(Option<usize>, &'static str)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a shared service as beeing initialized on the current thread until the guard is dropped.
+/// OnceCell::get_or_init deadlocks or panics if it's called re-entrantly, so recursions have to be
+/// detected before the cell is accessed.
+pub struct ResolutionGuard {
+    pushed: bool,
+}
+
+impl ResolutionGuard {
+    /// `key` has to identify the state of the shared service uniquely while it's initialized
+    pub fn enter(key: usize, type_name: &'static str) -> Result<Self, ResolveError> {
+        IN_PROGRESS.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if let Some(start) = stack.iter().position(|(k, _)| *k == Some(key)) {
+                let chain = stack[start + 1..]
+                    .iter()
+                    .map(|(_, name)| *name)
+                    .chain(core::iter::once(type_name))
+                    .fold(String::from(stack[start].1), |acc, n| acc + " -> " + n);
+                return Err(ResolveError::RecursiveResolution(chain));
+            }
+            stack.push((Some(key), type_name));
+            Ok(ResolutionGuard { pushed: true })
+        })
+    }
+
+    /// Marks a transient service as beeing resolved, so it's part of the chain if a shared service it depends on
+    /// resolves itself. Nothing is tracked unless a shared service is initialized, as the chain starts with one
+    pub fn enter_transient(type_name: &'static str) -> Self {
+        IN_PROGRESS.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let pushed = !stack.is_empty();
+            if pushed {
+                stack.push((None, type_name));
+            }
+            ResolutionGuard { pushed }
+        })
+    }
+}
+
+impl Drop for ResolutionGuard {
+    fn drop(&mut self) {
+        if self.pushed {
+            IN_PROGRESS.with(|stack| {
+                stack.borrow_mut().pop();
+            });
+        }
+    }
+}
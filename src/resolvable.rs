@@ -29,8 +29,10 @@ pub trait SealedResolvable {
         key: &Self::PrecheckResult,
     ) -> Self::ItemPreChecked;
 
-    fn precheck(ordered_types: &[TypeId]) -> Result<Self::PrecheckResult, BuildError>;
-    fn iter_positions(types: &[TypeId]) -> Self::TypeIdsIter;
+    fn precheck(ordered_types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError>;
+    fn iter_positions(types: &ServiceTypes) -> Self::TypeIdsIter;
+    /// Visits all dependencies independent of whether they are registered
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency));
 }
 
 impl SealedResolvable for () {
@@ -42,13 +44,15 @@ impl SealedResolvable for () {
     fn resolve(_: &ServiceProvider) -> Self::Item {}
     fn resolve_prechecked(_: &ServiceProvider, _: &Self::PrecheckResult) -> Self::ItemPreChecked {}
 
-    fn precheck(_ordered_types: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
+    fn precheck(_ordered_types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
         Ok(())
     }
 
-    fn iter_positions(_: &[TypeId]) -> Self::TypeIdsIter {
+    fn iter_positions(_: &ServiceTypes) -> Self::TypeIdsIter {
         core::iter::empty()
     }
+
+    fn visit_dependencies(_: &mut dyn FnMut(Dependency)) {}
 }
 impl Resolvable for () {}
 
@@ -72,15 +76,20 @@ impl<T0: Resolvable, T1: Resolvable> SealedResolvable for (T0, T1) {
         )
     }
 
-    fn precheck(ordered_types: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
+    fn precheck(ordered_types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
         let r0 = T0::precheck(ordered_types)?;
         let r1 = T1::precheck(ordered_types)?;
         Ok((r0, r1))
     }
 
-    fn iter_positions(types: &[TypeId]) -> Self::TypeIdsIter {
+    fn iter_positions(types: &ServiceTypes) -> Self::TypeIdsIter {
         T0::iter_positions(types).chain(T1::iter_positions(types))
     }
+
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        T0::visit_dependencies(visitor);
+        T1::visit_dependencies(visitor);
+    }
 }
 impl<T0: Resolvable, T1: Resolvable> Resolvable for (T0, T1) {}
 
@@ -109,18 +118,24 @@ impl<T0: Resolvable, T1: Resolvable, T2: Resolvable> SealedResolvable for (T0, T
         )
     }
 
-    fn precheck(ordered_types: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
+    fn precheck(ordered_types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
         let r0 = T0::precheck(ordered_types)?;
         let r1 = T1::precheck(ordered_types)?;
         let r2 = T2::precheck(ordered_types)?;
         Ok((r0, r1, r2))
     }
 
-    fn iter_positions(types: &[TypeId]) -> Self::TypeIdsIter {
+    fn iter_positions(types: &ServiceTypes) -> Self::TypeIdsIter {
         T0::iter_positions(types)
             .chain(T1::iter_positions(types))
             .chain(T2::iter_positions(types))
     }
+
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        T0::visit_dependencies(visitor);
+        T1::visit_dependencies(visitor);
+        T2::visit_dependencies(visitor);
+    }
 }
 impl<T0: Resolvable, T1: Resolvable, T2: Resolvable> Resolvable for (T0, T1, T2) {}
 
@@ -164,7 +179,7 @@ impl<T0: Resolvable, T1: Resolvable, T2: Resolvable, T3: Resolvable> SealedResol
         )
     }
 
-    fn precheck(ordered_types: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
+    fn precheck(ordered_types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
         let r0 = T0::precheck(ordered_types)?;
         let r1 = T1::precheck(ordered_types)?;
         let r2 = T2::precheck(ordered_types)?;
@@ -172,12 +187,19 @@ impl<T0: Resolvable, T1: Resolvable, T2: Resolvable, T3: Resolvable> SealedResol
         Ok((r0, r1, r2, r3))
     }
 
-    fn iter_positions(types: &[TypeId]) -> Self::TypeIdsIter {
+    fn iter_positions(types: &ServiceTypes) -> Self::TypeIdsIter {
         T0::iter_positions(types)
             .chain(T1::iter_positions(types))
             .chain(T2::iter_positions(types))
             .chain(T3::iter_positions(types))
     }
+
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        T0::visit_dependencies(visitor);
+        T1::visit_dependencies(visitor);
+        T2::visit_dependencies(visitor);
+        T3::visit_dependencies(visitor);
+    }
 }
 impl<T0: Resolvable, T1: Resolvable, T2: Resolvable, T3: Resolvable> Resolvable
     for (T0, T1, T2, T3)
@@ -194,16 +216,22 @@ impl SealedResolvable for WeakServiceProvider {
     fn resolve(_provider: &ServiceProvider) -> Self::Item {}
 
     fn resolve_prechecked(provider: &ServiceProvider, _: &()) -> Self::ItemPreChecked {
-        provider.into()
+        provider.weak(core::any::type_name::<WeakServiceProvider>())
     }
 
-    fn precheck(_: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
+    fn precheck(_: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
         Ok(())
     }
 
-    fn iter_positions(_types: &[TypeId]) -> Self::TypeIdsIter {
+    fn iter_positions(_types: &ServiceTypes) -> Self::TypeIdsIter {
         core::iter::empty()
     }
+
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        visitor(Dependency::new::<WeakServiceProvider, WeakServiceProvider>(
+            DependencyKind::ServiceProvider,
+        ))
+    }
 }
 impl Resolvable for WeakServiceProvider {}
 
@@ -219,56 +247,42 @@ unsafe fn resolve_unchecked<T: resolvable::Resolvable>(
     })(&provider)
 }
 
+/// position must be returned by the ServiceTypes of the provider
+unsafe fn resolve_position<T: resolvable::Resolvable>(
+    provider: &ServiceProvider,
+    position: ServicePosition,
+) -> T::ItemPreChecked {
+    match position {
+        ServicePosition::Own(pos) => resolve_unchecked::<T>(provider, pos),
+        // Inherited services are resolved by their own ServiceProvider
+        ServicePosition::Inherited(ancestor, pos) => resolve_unchecked::<T>(&*ancestor, pos),
+    }
+}
+
 impl<'a, T: resolvable::Resolvable> core::iter::Iterator for ServiceIterator<T> {
     type Item = T::ItemPreChecked;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.next_pos.map(|i| {
-            self.next_pos = if let Some(next) = self.provider.0.immutable_state.producers.get(i + 1)
-            {
-                if next.get_result_type_id() == &TypeId::of::<T>() {
-                    Some(i + 1)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            unsafe { resolve_unchecked::<T>(&self.provider.0, i) }
-        })
+        let position = self.positions.next()?;
+        Some(unsafe { resolve_position::<T>(&self.provider.0, position) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
     }
 
     fn last(self) -> Option<Self::Item>
     where
         Self: Sized,
     {
-        self.next_pos.map(|i| {
-            // If has_next, last must exist
-            let pos = binary_search::binary_search_last_by_key(
-                &self.provider.0.immutable_state.producers[i..],
-                &TypeId::of::<T>(),
-                |f| &f.get_result_type_id(),
-            )
-            .unwrap();
-            unsafe { resolve_unchecked::<T>(&self.provider.0, i + pos) }
-        })
+        let position = self.positions.last()?;
+        Some(unsafe { resolve_position::<T>(&self.provider.0, position) })
     }
     fn count(self) -> usize
     where
         Self: Sized,
     {
-        self.next_pos
-            .map(|i| {
-                let pos = binary_search::binary_search_last_by_key(
-                    &self.provider.0.immutable_state.producers[i..],
-                    &TypeId::of::<T>(),
-                    |f| &f.get_result_type_id(),
-                )
-                .unwrap();
-                pos + 1
-            })
-            .unwrap_or(0)
+        self.positions.count()
     }
 }
 
@@ -279,15 +293,14 @@ impl<T: Any> SealedResolvable for AllRegistered<T> {
     type TypeIdsIter = core::ops::Range<usize>;
 
     fn resolve(provider: &ServiceProvider) -> Self::Item {
-        let next_pos = binary_search::binary_search_first_by_key(
-            &provider.immutable_state.producers,
-            &TypeId::of::<Registered<T>>(),
-            |f| &f.get_result_type_id(),
-        );
+        let positions = provider
+            .immutable_state
+            .service_types()
+            .positions(TypeId::of::<Registered<T>>());
         ServiceIterator {
-            provider: provider.into(),
+            provider: provider.weak(core::any::type_name::<Self::Item>()),
             item_type: PhantomData,
-            next_pos,
+            positions,
         }
     }
 
@@ -298,30 +311,19 @@ impl<T: Any> SealedResolvable for AllRegistered<T> {
         Self::resolve(provider)
     }
 
-    fn precheck(_: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
+    fn precheck(_: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
         Ok(())
     }
 
-    fn iter_positions(types: &[TypeId]) -> Self::TypeIdsIter {
-        let first =
-            binary_search::binary_search_first_by_key(types, &TypeId::of::<Registered<T>>(), |f| {
-                &f
-            });
+    fn iter_positions(types: &ServiceTypes) -> Self::TypeIdsIter {
+        // Services of parents never depend on services of their children
+        types.own(TypeId::of::<Registered<T>>())
+    }
 
-        match first {
-            Some(x) => {
-                let to = binary_search::binary_search_last_by_key(
-                    &types[x..],
-                    &TypeId::of::<Registered<T>>(),
-                    |f| &f,
-                )
-                .unwrap()
-                    + x
-                    + 1;
-                x..to
-            }
-            None => 0..0,
-        }
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        visitor(Dependency::new::<Registered<T>, T>(
+            DependencyKind::AllRegistered,
+        ))
     }
 }
 impl<T: Any> Resolvable for AllRegistered<T> {}
@@ -329,42 +331,286 @@ impl<T: Any> Resolvable for AllRegistered<T> {}
 impl<T: Any> SealedResolvable for Registered<T> {
     type Item = Option<T>;
     type ItemPreChecked = T;
-    type PrecheckResult = usize;
-    type TypeIdsIter = core::iter::Once<usize>;
+    type PrecheckResult = ServicePosition;
+    type TypeIdsIter = core::option::IntoIter<usize>;
 
     fn resolve(provider: &ServiceProvider) -> Self::Item {
-        binary_search::binary_search_last_by_key(
-            &provider.immutable_state.producers,
-            &TypeId::of::<Self>(),
-            |f| &f.get_result_type_id(),
-        )
-        .map(|index| unsafe { resolve_unchecked::<Self>(provider, index) })
+        provider
+            .immutable_state
+            .service_types()
+            .last(TypeId::of::<Self>())
+            .map(|position| unsafe { resolve_position::<Self>(provider, position) })
     }
 
     fn resolve_prechecked(
         provider: &ServiceProvider,
-        index: &Self::PrecheckResult,
+        position: &Self::PrecheckResult,
     ) -> Self::ItemPreChecked {
-        unsafe { resolve_unchecked::<Self>(provider, *index) }
+        unsafe { resolve_position::<Self>(provider, *position) }
     }
 
-    fn precheck(producers: &[TypeId]) -> Result<Self::PrecheckResult, BuildError> {
-        binary_search::binary_search_last_by_key(&producers, &TypeId::of::<Self>(), |f| &f)
-            .ok_or_else(|| {
-                BuildError::MissingDependency(super::MissingDependencyType::new::<Self>())
-            })
+    fn precheck(types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
+        types
+            .last(TypeId::of::<Self>())
+            .ok_or_else(|| BuildError::MissingDependency(MissingDependencyType::new::<Self>()))
     }
 
-    fn iter_positions(types: &[TypeId]) -> Self::TypeIdsIter {
-        let position = binary_search::binary_search_last_by_key(
-            types,
-            &TypeId::of::<Self>(),
-            |f| &f
-        ).expect("Type not found. This shouldn't be possible, as MissingDependency should have been checked");
-        core::iter::once(position)
+    fn iter_positions(types: &ServiceTypes) -> Self::TypeIdsIter {
+        let position = types.last(TypeId::of::<Self>())
+            .expect("Type not found. This shouldn't be possible, as MissingDependency should have been checked");
+        position.own().into_iter()
+    }
+
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        visitor(Dependency::new::<Self, T>(DependencyKind::Registered))
     }
 }
 impl<T: Any> Resolvable for Registered<T> {}
+
+impl<T: Any> SealedResolvable for Inherited<T> {
+    type Item = Option<T>;
+    type ItemPreChecked = T;
+    type PrecheckResult = ServicePosition;
+    type TypeIdsIter = Empty<usize>;
+
+    fn resolve(provider: &ServiceProvider) -> Self::Item {
+        provider
+            .immutable_state
+            .service_types()
+            .last_inherited(TypeId::of::<Registered<T>>())
+            .map(|position| unsafe { resolve_position::<Registered<T>>(provider, position) })
+    }
+
+    fn resolve_prechecked(
+        provider: &ServiceProvider,
+        position: &Self::PrecheckResult,
+    ) -> Self::ItemPreChecked {
+        unsafe { resolve_position::<Registered<T>>(provider, *position) }
+    }
+
+    fn precheck(types: &ServiceTypes) -> Result<Self::PrecheckResult, BuildError> {
+        types
+            .last_inherited(TypeId::of::<Registered<T>>())
+            .ok_or_else(|| BuildError::MissingDependency(MissingDependencyType::new::<Self>()))
+    }
+
+    fn iter_positions(_types: &ServiceTypes) -> Self::TypeIdsIter {
+        // Inherited services are never part of a cycle, as parents cannot depend on their children
+        core::iter::empty()
+    }
+
+    fn visit_dependencies(visitor: &mut dyn FnMut(Dependency)) {
+        visitor(Dependency {
+            missing: MissingDependencyType::new::<Self>(),
+            ..Dependency::new::<Registered<T>, T>(DependencyKind::Inherited)
+        })
+    }
+}
+impl<T: Any> Resolvable for Inherited<T> {}
+
+/// Dependency of a service, which was declared with `ServiceCollection::with()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub(crate) kind: DependencyKind,
+    /// TypeId used to look up the dependency in ServiceTypes
+    pub(crate) type_id: TypeId,
+    pub(crate) type_name: &'static str,
+    /// Reported as `BuildError::MissingDependency`, if the dependency isn't registered
+    pub(crate) missing: MissingDependencyType,
+}
+
+impl Dependency {
+    fn new<TKey: Any, T: ?Sized>(kind: DependencyKind) -> Self {
+        Self {
+            kind,
+            type_id: TypeId::of::<TKey>(),
+            type_name: core::any::type_name::<T>(),
+            missing: MissingDependencyType::new::<TKey>(),
+        }
+    }
+}
+
+/// Resolvable used to declare a dependency
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DependencyKind {
+    Registered,
+    AllRegistered,
+    Inherited,
+    /// WeakServiceProvider
+    ServiceProvider,
+}
+
+/// Position of a service in the producers of a ServiceProvider or of one of its ancestors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServicePosition {
+    Own(usize),
+    /// The ancestor is kept alive by the parents of the ServiceProviderImmutableState, which returned this position
+    Inherited(*const ServiceProvider, usize),
+}
+
+// The ancestor is only accessed by shared references and ServiceProvider is Send + Sync
+unsafe impl Send for ServicePosition {}
+unsafe impl Sync for ServicePosition {}
+
+impl ServicePosition {
+    fn own(self) -> Option<usize> {
+        match self {
+            ServicePosition::Own(pos) => Some(pos),
+            ServicePosition::Inherited(..) => None,
+        }
+    }
+}
+
+/// Positions of the services returned by a ServiceIterator
+pub(crate) enum ServicePositions {
+    /// Avoids allocations for ServiceProviders without parents
+    Own(core::ops::Range<usize>),
+    Layered(alloc::vec::IntoIter<ServicePosition>),
+}
+
+impl Iterator for ServicePositions {
+    type Item = ServicePosition;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ServicePositions::Own(range) => range.next().map(ServicePosition::Own),
+            ServicePositions::Layered(positions) => positions.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            ServicePositions::Own(range) => range.size_hint(),
+            ServicePositions::Layered(positions) => positions.size_hint(),
+        }
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        match self {
+            ServicePositions::Own(range) => range.last().map(ServicePosition::Own),
+            ServicePositions::Layered(positions) => positions.last(),
+        }
+    }
+}
+
+/// Own types of a ServiceProvider ordered by TypeId and its parents, which are used to find the positions of dependencies.
+/// Services of parents are looked up in their own types, so children don't copy the services of their parents
+#[derive(Clone, Copy)]
+pub struct ServiceTypes<'a> {
+    pub(crate) ids: &'a [TypeId],
+    pub(crate) parents: &'a [WeakServiceProvider],
+}
+
+impl<'a> ServiceTypes<'a> {
+    /// Returns the positions of the own services registered with `id`
+    pub(crate) fn own(&self, id: TypeId) -> core::ops::Range<usize> {
+        own_positions(self.ids, id)
+    }
+
+    /// Returns the position of the service registered with `id`, which is resolved by `Registered<T>`
+    pub(crate) fn last(&self, id: TypeId) -> Option<ServicePosition> {
+        if self.parents.is_empty() {
+            return self.own(id).last().map(ServicePosition::Own);
+        }
+        let mut last = None;
+        self.for_each(id, &mut |position| last = Some(position));
+        last
+    }
+
+    /// Returns the position of the service registered with `id`, which is resolved by `Registered<T>`
+    /// in the parent providers
+    pub(crate) fn last_inherited(&self, id: TypeId) -> Option<ServicePosition> {
+        let mut last = None;
+        visit_layers(
+            0..0,
+            &|_| unreachable!(),
+            self.parents,
+            id,
+            &mut Vec::new(),
+            &mut |position, _| last = Some(position),
+        );
+        last
+    }
+
+    /// Returns the positions of all services registered with `id` in the order of `AllRegistered<T>`
+    pub(crate) fn positions(&self, id: TypeId) -> ServicePositions {
+        if self.parents.is_empty() {
+            return ServicePositions::Own(self.own(id));
+        }
+        let mut positions = Vec::new();
+        self.for_each(id, &mut |position| positions.push(position));
+        ServicePositions::Layered(positions.into_iter())
+    }
+
+    fn for_each(&self, id: TypeId, f: &mut dyn FnMut(ServicePosition)) {
+        visit_layers(
+            self.own(id),
+            &|pos| (ServicePosition::Own(pos), Sealing::Open),
+            self.parents,
+            id,
+            &mut Vec::new(),
+            &mut |position, _| f(position),
+        );
+    }
+}
+
+fn own_positions(ids: &[TypeId], id: TypeId) -> core::ops::Range<usize> {
+    match binary_search::binary_search_first_by_key(ids, &id, |f| f) {
+        Some(first) => {
+            let last = binary_search::binary_search_last_by_key(&ids[first..], &id, |f| f)
+                .expect("First exists, so last exists too");
+            first..first + last + 1
+        }
+        None => 0..0,
+    }
+}
+
+/// Calls `f` for all services registered with `id` in the order of `AllRegistered<T>`: Open services of the parents
+/// in the order the parents were added, `own` services and sealed services of the parents, which cannot be shadowed.
+/// Ancestors shared by multiple parents are only visited where they were reached first, like in `registrations()`
+fn visit_layers(
+    own: core::ops::Range<usize>,
+    to_position: &dyn Fn(usize) -> (ServicePosition, Sealing),
+    parents: &[WeakServiceProvider],
+    id: TypeId,
+    visited: &mut Vec<*const ServiceProviderMutableState>,
+    f: &mut dyn FnMut(ServicePosition, Sealing),
+) {
+    let mut sealed = Vec::new();
+    for parent in parents {
+        let states = Arc::as_ptr(&parent.0.service_states);
+        if visited.contains(&states) {
+            continue;
+        }
+        visited.push(states);
+        let state = &parent.0.immutable_state;
+        let ancestor: *const ServiceProvider = &parent.0;
+        visit_layers(
+            own_positions(&state.types, id),
+            &|pos| {
+                (
+                    ServicePosition::Inherited(ancestor, pos),
+                    state.metadata[pos].sealing,
+                )
+            },
+            &state.parents,
+            id,
+            visited,
+            &mut |position, sealing| match sealing {
+                Sealing::Open => f(position, sealing),
+                _ => sealed.push((position, sealing)),
+            },
+        );
+    }
+    for pos in own {
+        let (position, sealing) = to_position(pos);
+        f(position, sealing);
+    }
+    for (position, sealing) in sealed {
+        f(position, sealing);
+    }
+}
 #[cfg(test)]
 mod tests {
     use {super::*, alloc::vec};
@@ -377,9 +623,48 @@ mod tests {
             TypeId::of::<Registered<i64>>(),
         ];
         types.sort();
+        let types = ServiceTypes {
+            ids: &types,
+            parents: &[],
+        };
 
         assert_eq!(2, AllRegistered::<i32>::iter_positions(&types).count());
         assert_eq!(1, AllRegistered::<i64>::iter_positions(&types).count());
         assert_eq!(0, AllRegistered::<i128>::iter_positions(&types).count());
     }
+
+    #[test]
+    fn inherited_resolves_position_of_parent() {
+        let mut parent = ServiceCollection::new();
+        parent.register(|| 1i32);
+        parent.register(|| 2i32);
+        let parent = parent.build().unwrap();
+        let parents = [WeakServiceProvider::from(&parent)];
+        let mut ids = [
+            TypeId::of::<Registered<i32>>(),
+            TypeId::of::<Registered<u8>>(),
+        ];
+        ids.sort();
+        let own_i32 = ids
+            .iter()
+            .position(|id| *id == TypeId::of::<Registered<i32>>())
+            .unwrap();
+        let types = ServiceTypes {
+            ids: &ids,
+            parents: &parents,
+        };
+        let ancestor: *const ServiceProvider = &parents[0].0;
+
+        assert_eq!(
+            Ok(ServicePosition::Inherited(ancestor, 1)),
+            Inherited::<i32>::precheck(&types)
+        );
+        assert_eq!(
+            Ok(ServicePosition::Own(own_i32)),
+            Registered::<i32>::precheck(&types)
+        );
+        assert!(Inherited::<i64>::precheck(&types).is_err());
+        assert_eq!(0, Inherited::<i32>::iter_positions(&types).count());
+        assert_eq!(3, types.positions(TypeId::of::<Registered<i32>>()).count());
+    }
 }
@@ -1,9 +1,12 @@
 use {
     super::*,
-    crate::{ServiceCollection, ServiceProducer, ServiceProvider, ServiceProviderImmutableState},
+    crate::{
+        LeakPolicy, ServiceCollection, ServiceProducer, ServiceProvider,
+        ServiceProviderImmutableState,
+    },
+    alloc::collections::BTreeMap,
     alloc::sync::Arc,
-    core::{any::Any, clone::Clone, marker::PhantomData},
-    once_cell::sync::OnceCell,
+    core::{any::Any, clone::Clone, marker::PhantomData, mem::ManuallyDrop},
 };
 
 /// Performs all checks to build a ServiceProvider on premise that an instance of type T will be available.
@@ -22,12 +25,48 @@ use {
 /// assert_eq!(Some(1i64), provider1.get::<i64>());
 /// assert_eq!(Some(2i64), provider2.get::<i64>());
 /// ```
-pub struct ServiceProviderFactory<T: Any + Clone + Send + Sync> {
+pub struct ServiceProviderFactory<T: Any + Send + Sync> {
     service_states_count: usize,
     immutable_state: Arc<ServiceProviderImmutableState>,
+    leak_policy: Option<LeakPolicy>,
+    /// Converts the value passed to `build()` into the base, which is resolved by the base producers
+    into_base: fn(T) -> Box<dyn Any + Send + Sync>,
+    /// Shared services marked with `eager()`, which are initialized by `build()` and `build_pooled()`
+    eager: Vec<usize>,
     anticipated: PhantomData<T>,
 }
 
+/// Creates a ServiceProviderFactory, whose ServiceProviders inherit services from one or multiple parents.
+/// If a service is registered multiple times, `Registered<T>` prefers the base over the services registered
+/// in the child, which are preferred over the services of the parents (the parent added last wins).
+/// `AllRegistered<T>` returns the services of the parents in the order they were added, followed by the
+/// services of the child and the base:
+/// ```
+/// use {ioc_rs::ServiceCollection};
+///
+/// let mut infrastructure = ServiceCollection::new();
+/// infrastructure.register(|| "infrastructure");
+/// let infrastructure = infrastructure.build().expect("Configuration is valid");
+///
+/// let mut domain = ServiceCollection::new();
+/// domain.register(|| "domain");
+/// let domain = domain.build().expect("Configuration is valid");
+///
+/// let mut child = ServiceCollection::new();
+/// child.register(|| "child");
+/// let factory = child
+///     .with_parent(&infrastructure)
+///     .and_parent(&domain)
+///     .build_factory()
+///     .expect("Configuration is valid");
+///
+/// let provider = factory.build(());
+/// assert_eq!(Some("child"), provider.get::<&str>());
+/// assert_eq!(
+///     vec!["infrastructure", "domain", "child"],
+///     provider.get_all::<&str>().collect::<Vec<_>>()
+/// );
+/// ```
 pub struct ServiceProviderFactoryBuilder {
     collection: ServiceCollection,
     providers: Vec<WeakServiceProvider>,
@@ -40,64 +79,229 @@ impl ServiceProviderFactoryBuilder {
             providers: alloc::vec!(first_parent),
         }
     }
+    /// Adds another parent, which takes precedence over all parents added before
+    #[cfg_attr(feature = "track-leaks", track_caller)]
+    pub fn and_parent(mut self, provider: impl Into<WeakServiceProvider>) -> Self {
+        #[cfg(feature = "track-leaks")]
+        let _frame = crate::leak_tracking::enter_call(core::panic::Location::caller());
+        self.providers.push(provider.into());
+        self
+    }
     pub fn build_factory<T: Any + Clone + Send + Sync>(
         self,
     ) -> Result<ServiceProviderFactory<T>, super::BuildError> {
         ServiceProviderFactory::create(self.collection, self.providers)
     }
+    /// Same as `ServiceCollection::dependency_graph()`, but dependencies registered in the parents are part of the graph.
+    /// The base isn't known yet, so services depending on it are missing
+    pub fn dependency_graph(&self) -> super::DependencyGraph {
+        super::DependencyGraph::new(
+            self.collection
+                .producer_factories
+                .iter()
+                .map(|p| (p.type_id, &p.metadata)),
+            &self.providers,
+        )
+    }
+    pub fn build_tuple_factory<T: BaseValues>(
+        self,
+    ) -> Result<ServiceProviderFactory<T>, super::BuildError> {
+        ServiceProviderFactory::create_with_base(
+            self.collection,
+            self.providers,
+            T::producers(),
+            |base| Box::new(base),
+        )
+    }
+    pub fn build_shared_factory<T: Any + Send + Sync>(
+        self,
+    ) -> Result<ServiceProviderFactory<T>, super::BuildError> {
+        ServiceProviderFactory::create_shared(self.collection, self.providers)
+    }
+}
+
+/// Tuple of values, which are resolvable individually from ServiceProviders created by
+/// `ServiceCollection::build_tuple_factory()` (max tuple size == 4)
+pub trait BaseValues: Any + Clone + Send + Sync + SealedBaseValues {}
+
+pub trait SealedBaseValues {
+    /// Producers for all elements, which are resolved from the base value of the ServiceProvider
+    fn producers() -> Vec<BaseProducer>;
+}
+
+/// Wrapper to keep ServiceProducer private
+pub struct BaseProducer(ServiceProducer);
+
+impl<T0: Any + Clone + Send + Sync, T1: Any + Clone + Send + Sync> SealedBaseValues for (T0, T1) {
+    fn producers() -> Vec<BaseProducer> {
+        alloc::vec![
+            base_producer(|base: &Self| base.0.clone()),
+            base_producer(|base: &Self| base.1.clone()),
+        ]
+    }
+}
+impl<T0: Any + Clone + Send + Sync, T1: Any + Clone + Send + Sync> BaseValues for (T0, T1) {}
+
+impl<
+        T0: Any + Clone + Send + Sync,
+        T1: Any + Clone + Send + Sync,
+        T2: Any + Clone + Send + Sync,
+    > SealedBaseValues for (T0, T1, T2)
+{
+    fn producers() -> Vec<BaseProducer> {
+        alloc::vec![
+            base_producer(|base: &Self| base.0.clone()),
+            base_producer(|base: &Self| base.1.clone()),
+            base_producer(|base: &Self| base.2.clone()),
+        ]
+    }
+}
+impl<
+        T0: Any + Clone + Send + Sync,
+        T1: Any + Clone + Send + Sync,
+        T2: Any + Clone + Send + Sync,
+    > BaseValues for (T0, T1, T2)
+{
+}
+
+impl<
+        T0: Any + Clone + Send + Sync,
+        T1: Any + Clone + Send + Sync,
+        T2: Any + Clone + Send + Sync,
+        T3: Any + Clone + Send + Sync,
+    > SealedBaseValues for (T0, T1, T2, T3)
+{
+    fn producers() -> Vec<BaseProducer> {
+        alloc::vec![
+            base_producer(|base: &Self| base.0.clone()),
+            base_producer(|base: &Self| base.1.clone()),
+            base_producer(|base: &Self| base.2.clone()),
+            base_producer(|base: &Self| base.3.clone()),
+        ]
+    }
+}
+impl<
+        T0: Any + Clone + Send + Sync,
+        T1: Any + Clone + Send + Sync,
+        T2: Any + Clone + Send + Sync,
+        T3: Any + Clone + Send + Sync,
+    > BaseValues for (T0, T1, T2, T3)
+{
+}
+
+/// Fails, if a parent or the child registers a service, which was sealed by a parent added before.
+/// Only sealed types are looked up in the parents, so their services aren't enumerated
+fn check_sealed_services(
+    parents: &[WeakServiceProvider],
+    child_producers: &[ServiceProducer],
+) -> Result<(), super::BuildError> {
+    let mut sealed = BTreeMap::new();
+    for parent in parents {
+        let types = parent.0.immutable_state.service_types();
+        if let Some(name) = sealed
+            .iter()
+            .find(|(id, _)| types.last(**id).is_some())
+            .map(|(_, name)| *name)
+        {
+            return Err(super::BuildError::OverrideOfSealedService(String::from(
+                name,
+            )));
+        }
+        sealed.extend(parent.0.immutable_state.sealed_types.iter());
+    }
+    match child_producers
+        .iter()
+        .find(|producer| sealed.contains_key(&producer.type_id))
+    {
+        Some(producer) => Err(super::BuildError::OverrideOfSealedService(
+            producer.metadata.type_name.into(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Resolves `TValue` from the base value `T` of a ServiceProvider created by a ServiceProviderFactory
+fn base_producer<T: Any, TValue: Any>(get: fn(&T) -> TValue) -> BaseProducer {
+    let factory: crate::UntypedFnFactory = Box::new(move |_service_state_counter| {
+        let creator: Box<dyn Fn(&ServiceProvider) -> TValue> =
+            Box::new(move |provider| match &provider.service_states.base {
+                Some(x) => get(x.downcast_ref::<T>().unwrap()),
+                None => panic!("Expected ServiceProviderFactory to set a value for `base`"),
+            });
+        Ok(creator.into())
+    });
+    BaseProducer(ServiceProducer::new::<TValue>(
+        factory,
+        crate::ServiceLifetime::Base,
+    ))
 }
 
 impl<T: Any + Clone + Send + Sync> ServiceProviderFactory<T> {
     pub fn create(
-        mut collection: ServiceCollection,
+        collection: ServiceCollection,
         parents: Vec<WeakServiceProvider>,
     ) -> Result<Self, super::BuildError> {
-        let parent_service_factories: Vec<_> = parents
-            .iter()
-            .flat_map(|parent| {
-                parent
-                    .0
-                    .immutable_state
-                    .producers
-                    .iter()
-                    .zip(parent.0.immutable_state.types.iter())
-                    .map(move |(parent_producer, parent_type)| {
-                        // parents are part of ServiceProviderImmutableState to live as long as the inherited UntypedFn
-                        let factory = unsafe { parent_producer.bind(&parent.0) };
-                        ServiceProducer::new_with_type(Box::new(move |_| Ok(factory)), *parent_type)
-                    })
-            })
-            .collect();
-
-        let factory: crate::UntypedFnFactory = Box::new(move |_service_state_counter| {
-            let creator: Box<dyn Fn(&ServiceProvider) -> T> =
-                Box::new(move |provider| match &provider.service_states.base {
-                    Some(x) => x.downcast_ref::<T>().unwrap().clone(),
-                    None => panic!("Expected ServiceProviderFactory to set a value for `base`"),
-                });
-            Ok(creator.into())
-        });
+        Self::create_with_base(
+            collection,
+            parents,
+            alloc::vec![base_producer(|base: &T| base.clone())],
+            |base| Box::new(base),
+        )
+    }
+}
+
+impl<T: Any + Send + Sync> ServiceProviderFactory<T> {
+    /// The base is moved into an Arc once per ServiceProvider and resolvable as `Arc<T>`, so T doesn't need to be Clone
+    pub(crate) fn create_shared(
+        collection: ServiceCollection,
+        parents: Vec<WeakServiceProvider>,
+    ) -> Result<Self, super::BuildError> {
+        Self::create_with_base(
+            collection,
+            parents,
+            alloc::vec![base_producer(|base: &Arc<T>| base.clone())],
+            |base| Box::new(Arc::new(base)),
+        )
+    }
 
+    pub(crate) fn create_with_base(
+        mut collection: ServiceCollection,
+        parents: Vec<WeakServiceProvider>,
+        base_producers: Vec<BaseProducer>,
+        into_base: fn(T) -> Box<dyn Any + Send + Sync>,
+    ) -> Result<Self, super::BuildError> {
         collection
             .producer_factories
-            .push(ServiceProducer::new::<T>(factory));
+            .extend(base_producers.into_iter().map(|p| p.0));
+        check_sealed_services(&parents, &collection.producer_factories)?;
 
-        let (producers, types, service_states_count) =
-            collection.validate_producers(parent_service_factories)?;
-
-        let immutable_state = Arc::new(ServiceProviderImmutableState {
-            producers,
-            types,
-            _parents: parents,
-        });
+        let leak_policy = collection.leak_policy;
+        let (immutable_state, service_states_count) = collection
+            .validate_producers(parents, false)
+            .map_err(super::BuildErrors::into_first)?;
 
+        let eager = crate::initialization::eager_order(&immutable_state.metadata);
+        #[cfg(feature = "std")]
+        let immutable_state = crate::ServiceProviderImmutableState {
+            pool: Some(crate::state_pool::StatePool::new(service_states_count)),
+            ..immutable_state
+        };
         Ok(ServiceProviderFactory {
             service_states_count,
-            immutable_state,
+            immutable_state: Arc::new(immutable_state),
+            leak_policy,
+            into_base,
+            eager,
             anticipated: PhantomData,
         })
     }
 
+    /// Defines how ServiceProviders created by this factory handle leaks when they are dropped.
+    /// Overrides the LeakPolicy of the ServiceCollection
+    pub fn set_leak_policy(&mut self, policy: LeakPolicy) {
+        self.leak_policy = Some(policy);
+    }
+
     /// The ServiceProvider should always be assigned to a variable.
     /// Otherwise, a requested shared service it will outlive its ServiceProvider,
     /// resulting in a panic if debug_assertions are enabled
@@ -116,17 +320,61 @@ impl<T: Any + Clone + Send + Sync> ServiceProviderFactory<T> {
     /// # }
     /// ```
     pub fn build(&self, remaining: T) -> ServiceProvider {
-        let shared_services = alloc::vec![OnceCell::new(); self.service_states_count];
+        let provider = ServiceProvider {
+            service_states: ManuallyDrop::new(Arc::new(ServiceProviderMutableState::new(
+                self.service_states_count,
+                Some((self.into_base)(remaining)),
+            ))),
+            immutable_state: self.immutable_state.clone(),
+            is_root: true,
+            leak_policy: self.leak_policy,
+            #[cfg(feature = "std")]
+            pooled: false,
+            #[cfg(feature = "track-leaks")]
+            origin_id: None,
+        };
+        provider.initialize_eager(&self.eager);
+        provider
+    }
 
-        ServiceProvider {
-            service_states: Arc::new(ServiceProviderMutableState {
-                shared_services,
-                base: Some(Box::new(remaining)),
-            }),
+    /// Same as `build()`, but reuses the storage for shared services of ServiceProviders, which were dropped before.
+    /// The storage is only recycled, if the ServiceProvider passed its leak check, so nothing can reference
+    /// the shared services of the previous ServiceProvider anymore. Up to 16 states are kept per factory:
+    /// ```
+    /// use {ioc_rs::{Registered, ServiceCollection}, std::sync::Arc};
+    ///
+    /// let mut collection = ServiceCollection::new();
+    /// collection
+    ///     .with::<Registered<i32>>()
+    ///     .register_shared(|request_id| Arc::new(request_id as i64));
+    /// let factory = collection.build_factory().expect("Configuration is valid");
+    ///
+    /// for request_id in 0..3 {
+    ///     let provider = factory.build_pooled(request_id);
+    ///     assert_eq!(Some(request_id as i64), provider.get::<Arc<i64>>().map(|id| *id));
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn build_pooled(&self, remaining: T) -> ServiceProvider {
+        let provider = ServiceProvider {
+            service_states: ManuallyDrop::new(self.pool().take((self.into_base)(remaining))),
             immutable_state: self.immutable_state.clone(),
-            #[cfg(debug_assertions)]
             is_root: true,
-        }
+            leak_policy: self.leak_policy,
+            pooled: true,
+            #[cfg(feature = "track-leaks")]
+            origin_id: None,
+        };
+        provider.initialize_eager(&self.eager);
+        provider
+    }
+
+    #[cfg(feature = "std")]
+    fn pool(&self) -> &crate::state_pool::StatePool {
+        self.immutable_state
+            .pool
+            .as_ref()
+            .expect("ServiceProviderFactory always creates a pool")
     }
 }
 
@@ -158,6 +406,226 @@ mod tests {
         assert_eq!(alloc::vec!(0, 1, 2), iterator.collect::<Vec<_>>());
     }
 
+    #[test]
+    fn later_parents_take_precedence() {
+        let mut first_collection = ServiceCollection::new();
+        first_collection.register(|| 1);
+        first_collection.register(|| 1u8);
+        let first = first_collection.build().unwrap();
+
+        let mut second_collection = ServiceCollection::new();
+        second_collection.register(|| 2);
+        second_collection.register(|| 2u16);
+        let second = second_collection.build().unwrap();
+
+        let child_provider = ServiceCollection::new()
+            .with_parent(&first)
+            .and_parent(&second)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        assert_eq!(Some(2), child_provider.get::<i32>());
+        assert_eq!(Some(1u8), child_provider.get::<u8>());
+        assert_eq!(Some(2u16), child_provider.get::<u16>());
+        assert_eq!(
+            alloc::vec!(1, 2),
+            child_provider.get_all::<i32>().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fail_if_child_overrides_sealed_service() {
+        let mut parent_collection = ServiceCollection::new();
+        parent_collection.register(|| 1).sealed();
+        let parent = parent_collection.build().unwrap();
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection.register(|| 2);
+        assert!(matches!(
+            child_collection.with_parent(&parent).build_factory::<()>(),
+            Err(BuildError::OverrideOfSealedService(name)) if name == "i32"
+        ));
+        assert!(matches!(
+            ServiceCollection::new()
+                .with_parent(&parent)
+                .build_factory::<i32>(),
+            Err(BuildError::OverrideOfSealedService(_))
+        ));
+    }
+
+    #[test]
+    fn fail_if_child_overrides_sealed_alias() {
+        trait Counter: Send + Sync {}
+        impl Counter for AtomicI32 {}
+
+        let mut parent_collection = ServiceCollection::new();
+        parent_collection
+            .register_shared(|| Arc::new(AtomicI32::new(1)))
+            .alias(|c| c as Arc<dyn Counter>)
+            .sealed();
+        let parent = parent_collection.build().unwrap();
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection.register(|| Arc::new(AtomicI32::new(2)) as Arc<dyn Counter>);
+        assert!(matches!(
+            child_collection.with_parent(&parent).build_factory::<()>(),
+            Err(BuildError::OverrideOfSealedService(_))
+        ));
+    }
+
+    #[test]
+    fn only_sealed_alias_of_alias_chain_is_sealed() {
+        trait Counter: Send + Sync {}
+        impl Counter for AtomicI32 {}
+        struct Wrapped(#[allow(dead_code)] Arc<dyn Counter>);
+
+        let mut parent_collection = ServiceCollection::new();
+        parent_collection
+            .register_shared(|| Arc::new(AtomicI32::new(1)))
+            .alias(|c| c as Arc<dyn Counter>)
+            .alias(Wrapped)
+            .sealed();
+        let parent = parent_collection.build().unwrap();
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection.register(|| Arc::new(AtomicI32::new(2)) as Arc<dyn Counter>);
+        assert!(child_collection
+            .with_parent(&parent)
+            .build_factory::<()>()
+            .is_ok());
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection.register(|| Wrapped(Arc::new(AtomicI32::new(2))));
+        assert!(matches!(
+            child_collection.with_parent(&parent).build_factory::<()>(),
+            Err(BuildError::OverrideOfSealedService(_))
+        ));
+    }
+
+    #[test]
+    fn fail_if_later_parent_overrides_sealed_service() {
+        let mut first_collection = ServiceCollection::new();
+        first_collection.register(|| 1).sealed();
+        let first = first_collection.build().unwrap();
+        let mut second_collection = ServiceCollection::new();
+        second_collection.register(|| 2);
+        let second = second_collection.build().unwrap();
+
+        assert!(matches!(
+            ServiceCollection::new()
+                .with_parent(&second)
+                .and_parent(&first)
+                .build_factory::<()>()
+                .map(|f| f.build(()).get::<i32>()),
+            Ok(Some(1))
+        ));
+        assert!(matches!(
+            ServiceCollection::new()
+                .with_parent(&first)
+                .and_parent(&second)
+                .build_factory::<()>(),
+            Err(BuildError::OverrideOfSealedService(_))
+        ));
+    }
+
+    #[test]
+    fn children_add_services_to_sealed_service_allowing_additions() {
+        let mut parent_collection = ServiceCollection::new();
+        parent_collection.register(|| 1).sealed_allowing_additions();
+        let parent = parent_collection.build().unwrap();
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection.register(|| 2);
+        let grandchild_parent = child_collection
+            .with_parent(&parent)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        let mut grandchild_collection = ServiceCollection::new();
+        grandchild_collection.register(|| 3);
+        let provider = grandchild_collection
+            .with_parent(&grandchild_parent)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        assert_eq!(Some(1), provider.get::<i32>());
+        assert_eq!(
+            alloc::vec!(2, 3, 1),
+            provider.get_all::<i32>().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn inherited_ignores_services_of_the_child() {
+        let mut parent_collection = ServiceCollection::new();
+        parent_collection.register(|| 1);
+        let parent = parent_collection.build().unwrap();
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection.register(|| 2);
+        child_collection
+            .with::<crate::Inherited<i32>>()
+            .register(|i| i as i64);
+        let provider = child_collection
+            .with_parent(&parent)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        assert_eq!(Some(1i64), provider.get::<i64>());
+        assert_eq!(Some(1), provider.resolve::<crate::Inherited<i32>>());
+
+        let mut orphan_collection = ServiceCollection::new();
+        orphan_collection.register(|| 2);
+        orphan_collection
+            .with::<crate::Inherited<i32>>()
+            .register(|i| i as i64);
+        assert!(matches!(
+            orphan_collection.build(),
+            Err(BuildError::MissingDependency(_))
+        ));
+    }
+
+    #[test]
+    fn grandchildren_resolve_services_of_grandparents_without_copying_them() {
+        let mut grandparent_collection = ServiceCollection::new();
+        grandparent_collection.register_shared(|| Arc::new(AtomicI32::new(42)));
+        grandparent_collection.register(|| 1);
+        let grandparent = grandparent_collection.build().unwrap();
+
+        let mut parent_collection = ServiceCollection::new();
+        parent_collection.register(|| 2);
+        let parent = parent_collection
+            .with_parent(&grandparent)
+            .build_factory::<()>()
+            .unwrap()
+            .build(());
+
+        let mut child_collection = ServiceCollection::new();
+        child_collection
+            .with::<Registered<Arc<AtomicI32>>>()
+            .register(|i| i.load(Ordering::Relaxed) as i64);
+        let factory = child_collection
+            .with_parent(&parent)
+            .build_factory::<()>()
+            .unwrap();
+        assert_eq!(2, factory.immutable_state.producers.len());
+
+        let provider = factory.build(());
+        assert_eq!(Some(42i64), provider.get::<i64>());
+        assert_eq!(
+            alloc::vec!(1, 2),
+            provider.get_all::<i32>().collect::<Vec<_>>()
+        );
+        assert!(Arc::ptr_eq(
+            &grandparent.get::<Arc<AtomicI32>>().unwrap(),
+            &provider.get::<Arc<AtomicI32>>().unwrap()
+        ));
+    }
+
     #[test]
     fn uses_same_parent_arc_for_two_providers_from_the_same_child_factory() {
         let mut parent_provider = ServiceCollection::new();
@@ -228,6 +696,92 @@ mod tests {
         assert_eq!(Ok((2, 1)), result);
     }
 
+    #[test]
+    fn pooled_providers_recycle_state_only_without_leaks() {
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<Registered<i32>>()
+            .register_shared(|i| Arc::new(i as i64));
+        collection.set_leak_policy(crate::LeakPolicy::Ignore);
+        let factory = collection.build_factory().unwrap();
+
+        let first = factory.build_pooled(1);
+        assert_eq!(Some(Arc::new(1i64)), first.get::<Arc<i64>>());
+        let first_state = Arc::as_ptr(&first.service_states);
+        drop(first);
+        assert_eq!(1, factory.pool().len());
+
+        let second = factory.build_pooled(2);
+        assert_eq!(first_state, Arc::as_ptr(&second.service_states));
+        assert_eq!(0, factory.pool().len());
+        assert_eq!(Some(Arc::new(2i64)), second.get::<Arc<i64>>());
+        let leaked = second.get::<Arc<i64>>();
+        drop(second);
+        assert_eq!(0, factory.pool().len());
+
+        let third = factory.build_pooled(3);
+        assert_eq!(Some(Arc::new(3i64)), third.get::<Arc<i64>>());
+        assert_eq!(Some(Arc::new(2i64)), leaked);
+    }
+
+    #[test]
+    fn pool_keeps_limited_number_of_states() {
+        let mut collection = ServiceCollection::new();
+        collection.register_shared(|| Arc::new(1i64));
+        let factory = collection.build_factory().unwrap();
+
+        let providers: Vec<_> = (0..20).map(|i| factory.build_pooled(i)).collect();
+        drop(providers);
+        assert_eq!(16, factory.pool().len());
+
+        let providers: Vec<_> = (0..20).map(|i| factory.build_pooled(i)).collect();
+        assert_eq!(0, factory.pool().len());
+        assert_eq!(Some(19), providers[19].get::<i32>());
+    }
+
+    #[test]
+    fn resolve_tuple_elements_individually() {
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<(Registered<i32>, Registered<&'static str>)>()
+            .register(|(number, text)| alloc::format!("{} {}", text, number));
+        let factory = collection
+            .build_tuple_factory::<(i32, &'static str, u8)>()
+            .unwrap();
+
+        let provider = factory.build((1, "first", 2));
+        assert_eq!(
+            Some("first 1".into()),
+            provider.get::<alloc::string::String>()
+        );
+        assert_eq!(Some(2u8), provider.get::<u8>());
+        assert_eq!(None, provider.get::<(i32, &'static str, u8)>());
+        assert_eq!(
+            Some("second 2".into()),
+            factory
+                .build((2, "second", 0))
+                .get::<alloc::string::String>()
+        );
+    }
+
+    #[test]
+    fn resolve_non_clone_base_as_arc() {
+        struct Socket(u16);
+
+        let mut collection = ServiceCollection::new();
+        collection
+            .with::<Registered<Arc<Socket>>>()
+            .register(|socket| socket.0);
+        let factory = collection.build_shared_factory::<Socket>().unwrap();
+
+        let provider = factory.build(Socket(80));
+        let first = provider.get::<Arc<Socket>>().unwrap();
+        let second = provider.get::<Arc<Socket>>().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(Some(80u16), provider.get::<u16>());
+        assert_eq!(Some(443u16), factory.build(Socket(443)).get::<u16>());
+    }
+
     #[test]
     fn create_provider_with_factory() {
         let mut collection = ServiceCollection::new();
@@ -242,14 +796,18 @@ mod tests {
     fn create_provider_with_factory_fails_for_missing_dependency() {
         let mut collection = ServiceCollection::new();
         collection.with::<Registered<i32>>().register(|s| s as i64);
+        let line = line!() - 1;
         if let Err(BuildError::MissingDependency(infos)) = collection.build_factory::<u32>() {
             assert_eq!(
                 infos,
                 crate::MissingDependencyType {
                     id: core::any::TypeId::of::<Registered<i32>>(),
-                    name: "ioc_rs::Registered<i32>"
+                    name: "ioc_rs::Registered<i32>",
+                    requested_by: "i64",
+                    registered_at: infos.registered_at,
                 }
             );
+            assert_eq!(Some(line), infos.registered_at().map(|l| l.line()));
         } else {
             panic!("Expected to have missing dependency error");
         }
@@ -0,0 +1,95 @@
+use {
+    crate::ServiceProviderMutableState,
+    alloc::{boxed::Box, sync::Arc},
+    core::{
+        any::Any,
+        ptr,
+        sync::atomic::{AtomicPtr, Ordering},
+    },
+};
+
+/// Maximum number of states kept by a pool. States of further ServiceProviders are dropped
+const SLOTS: usize = 16;
+
+/// States of dropped ServiceProviders created by `ServiceProviderFactory::build_pooled()`, which are reused to
+/// avoid allocating the shared service storage per ServiceProvider. The pool keeps as many states as there
+/// were pooled ServiceProviders alive at the same time, up to `SLOTS`.
+///
+/// Slots are taken and filled with atomic swaps instead of a Mutex, as the pool is accessed once
+/// when a pooled ServiceProvider is built and once when it's dropped
+pub(crate) struct StatePool {
+    shared_services_count: usize,
+    /// Null or a state created by `Arc::into_raw()`, which was the only reference when it was recycled
+    slots: [AtomicPtr<ServiceProviderMutableState>; SLOTS],
+}
+
+impl StatePool {
+    pub fn new(shared_services_count: usize) -> Self {
+        Self {
+            shared_services_count,
+            slots: Default::default(),
+        }
+    }
+
+    /// Returns a recycled state if available, a new one otherwise
+    pub fn take(&self, base: Box<dyn Any + Send + Sync>) -> Arc<ServiceProviderMutableState> {
+        for slot in &self.slots {
+            if slot.load(Ordering::Relaxed).is_null() {
+                continue;
+            }
+            let pointer = slot.swap(ptr::null_mut(), Ordering::Acquire);
+            if !pointer.is_null() {
+                // The slot owned the only reference, which was moved out by the swap
+                let mut state = unsafe { Arc::from_raw(pointer) };
+                Arc::get_mut(&mut state)
+                    .expect("Recycled states aren't referenced anywhere else")
+                    .base = Some(base);
+                return state;
+            }
+        }
+        Arc::new(ServiceProviderMutableState::new(
+            self.shared_services_count,
+            Some(base),
+        ))
+    }
+
+    /// Keeps the state for the next ServiceProvider, if nothing else references it anymore and a slot is free.
+    /// Must be called after its shared services were disposed
+    pub fn recycle(&self, mut state: Arc<ServiceProviderMutableState>) {
+        if let Some(mutable) = Arc::get_mut(&mut state) {
+            mutable.reset();
+            let pointer = Arc::into_raw(state) as *mut ServiceProviderMutableState;
+            let stored = self.slots.iter().any(|slot| {
+                slot.compare_exchange(
+                    ptr::null_mut(),
+                    pointer,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            });
+            if !stored {
+                drop(unsafe { Arc::from_raw(pointer) });
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.slots
+            .iter()
+            .filter(|slot| !slot.load(Ordering::Relaxed).is_null())
+            .count()
+    }
+}
+
+impl Drop for StatePool {
+    fn drop(&mut self) {
+        for slot in &mut self.slots {
+            let pointer = *slot.get_mut();
+            if !pointer.is_null() {
+                drop(unsafe { Arc::from_raw(pointer) });
+            }
+        }
+    }
+}
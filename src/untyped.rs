@@ -8,7 +8,6 @@ use {
 pub struct UntypedFn {
     result_type_id: TypeId, // Todo: Debug only
     pointer: *mut dyn Fn(),
-    wrapper_creator: unsafe fn(*const UntypedFn, *const ServiceProvider) -> UntypedFn,
 }
 
 unsafe impl Send for UntypedFn {}
@@ -25,13 +24,6 @@ impl UntypedFn {
         debug_assert_eq!(TypeId::of::<Registered<T>>(), self.result_type_id);
         &*(self.pointer as *const dyn Fn(&ServiceProvider) -> T)
     }
-
-    /// Creates a UntypedFn which ignores it's passed ServiceProvider and always uses the one it's bound to
-    /// Unsafe constraint: `&self` and the value behind `&ServiceProvider` must live longer than the
-    /// returned UntypedFn
-    pub unsafe fn bind(&self, provider: *const ServiceProvider) -> Self {
-        (self.wrapper_creator)(self, provider)
-    }
 }
 
 impl<T> From<Box<dyn Fn(&ServiceProvider) -> T>> for UntypedFn
@@ -42,11 +34,6 @@ where
         UntypedFn {
             result_type_id: core::any::TypeId::of::<Registered<T>>(),
             pointer: Box::into_raw(factory) as *mut dyn Fn(),
-            wrapper_creator: |inner, provider| {
-                let factory: Box<dyn Fn(&ServiceProvider) -> T> =
-                    Box::new(move |_| unsafe { ((&*inner).borrow_for::<T>())(&*provider) });
-                factory.into()
-            },
         }
     }
 }
@@ -57,8 +44,7 @@ impl Drop for UntypedFn {
     }
 }
 
-#[cfg(debug_assertions)]
-type UntypedPointerChecker = Option<Box<dyn Fn() -> DanglingCheckerResult>>;
+type UntypedPointerChecker = Option<Box<dyn Fn() -> LeakedService>>;
 
 #[derive(Clone)]
 pub struct UntypedPointer {
@@ -66,7 +52,6 @@ pub struct UntypedPointer {
     destroyer: fn(*mut ()),
     #[cfg(debug_assertions)]
     debug_type: TypeId,
-    #[cfg(debug_assertions)]
     checker: fn(*mut ()) -> UntypedPointerChecker,
 }
 
@@ -74,18 +59,26 @@ pub struct UntypedPointer {
 unsafe impl Send for UntypedPointer {}
 unsafe impl Sync for UntypedPointer {}
 
-pub struct DanglingCheckerResult {
+/// A shared service which is still referenced after its ServiceProvider was dropped or closed
+#[derive(Clone, PartialEq, Eq)]
+pub struct LeakedService {
     pub remaining_references: usize,
     pub typename: &'static str,
+    /// Call to `register*()`, which registered the service
+    pub registered_at: Option<&'static core::panic::Location<'static>>,
 }
 
-impl core::fmt::Debug for DanglingCheckerResult {
+impl core::fmt::Debug for LeakedService {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "Type: {} (remaining {})",
             self.typename, self.remaining_references
-        )
+        )?;
+        if let Some(location) = self.registered_at {
+            write!(f, " registered at {}", location)?;
+        }
+        Ok(())
     }
 }
 
@@ -99,15 +92,15 @@ impl UntypedPointer {
             #[cfg(debug_assertions)]
             debug_type: TypeId::of::<Arc<T>>(),
 
-            #[cfg(debug_assertions)]
             checker: |x| {
                 let arc_ref: &Arc<T> = unsafe { &*(x as *mut Arc<T>) };
                 let count = Arc::strong_count(arc_ref);
                 if count > 1 {
                     let weak = Arc::downgrade(arc_ref);
-                    Some(Box::new(move || DanglingCheckerResult {
+                    Some(Box::new(move || LeakedService {
                         remaining_references: weak.strong_count(),
                         typename: core::any::type_name::<T>(),
+                        registered_at: None,
                     }))
                 } else {
                     None
@@ -122,8 +115,7 @@ impl UntypedPointer {
     /// Returns a lambda which can be called even after the UntypedPointer is destroyed
     /// The checker is just created, if the strong_count > 1. Because this method is used in the desturctor of ServiceProvider,
     /// the pointer which is causing > 1 is held by the ServiceProvider itself.
-    #[cfg(debug_assertions)]
-    pub fn get_weak_checker_if_dangling(&self) -> Option<Box<dyn Fn() -> DanglingCheckerResult>> {
+    pub fn get_weak_checker_if_dangling(&self) -> Option<Box<dyn Fn() -> LeakedService>> {
         (self.checker)(self.pointer)
     }
 }
@@ -135,7 +127,6 @@ impl Default for UntypedPointer {
             destroyer: |_| {},
             #[cfg(debug_assertions)]
             debug_type: TypeId::of::<()>(),
-            #[cfg(debug_assertions)]
             checker: |_| None,
         }
     }
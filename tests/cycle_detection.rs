@@ -9,7 +9,11 @@ fn handle_cyclic_references() {
 
     let err = col.build().expect_err("Expected to return error");
     let msg = match err {
-        BuildError::CyclicDependency(msg) => msg,
+        BuildError::CyclicDependency(cycle) => cycle
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(" -> "),
         _ => panic!("Expected BuildError::CyclicDependency"),
     };
     assert!(msg.contains("i32 -> i16"));
@@ -22,12 +26,44 @@ fn one_of_multiple_dependencies_asks_for_dependent_should_trigger_cyclic_depende
     let mut col = ServiceCollection::new();
     col.register(|| 0i32);
     col.with::<Registered<i64>>().register(|_| 1i32);
+    let i32_line = line!() - 1;
     col.register(|| 2i32);
 
     col.with::<AllRegistered<i32>>().register(|_| 42i64);
+    let i64_line = line!() - 1;
+    let err = col.build().expect_err("Expected to return error");
+    let (cycle, locations): (Vec<_>, Vec<_>) = match &err {
+        BuildError::CyclicDependency(cycle) => cycle.iter().copied().unzip(),
+        _ => panic!("Expected BuildError::CyclicDependency"),
+    };
+    assert_eq!(
+        vec![
+            "ioc_rs::ServiceIterator<ioc_rs::Registered<i32>>",
+            "i64",
+            "ioc_rs::ServiceIterator<ioc_rs::Registered<i32>>"
+        ],
+        cycle
+    );
+    // Registrations of the i32 service resolved by the iterator and of the i64 service closing the cycle
+    let lines: Vec<_> = locations
+        .iter()
+        .map(|l| l.map(|l| (l.file(), l.line())))
+        .collect();
     assert_eq!(
-        col.build().expect_err("Expected to return error"),
-        BuildError::CyclicDependency("ioc_rs::ServiceIterator<ioc_rs::Registered<i32>> -> i64 -> ioc_rs::ServiceIterator<ioc_rs::Registered<i32>>".to_owned())
+        vec![
+            Some((file!(), i32_line)),
+            Some((file!(), i64_line)),
+            Some((file!(), i32_line))
+        ],
+        lines
+    );
+    assert_eq!(
+        format!(
+            "ioc_rs::ServiceIterator<ioc_rs::Registered<i32>> -> i64 -> ioc_rs::ServiceIterator<ioc_rs::Registered<i32>> (registered at {}, {})",
+            locations[0].unwrap(),
+            locations[1].unwrap()
+        ),
+        err.to_string()
     );
 }
 
@@ -41,3 +77,47 @@ fn service_a_depends_on_other_which_has_reference_to_typeof_a_but_a_is_not_last_
     col.build()
         .expect("Expecting constellation to be resolvable");
 }
+
+#[test]
+fn build_all_errors_reports_independent_cycles_and_missing_dependencies() {
+    let mut col = ServiceCollection::new();
+    col.with::<Registered<i16>>().register(|_| 0i8);
+    col.with::<Registered<i8>>().register(|_| 0i16);
+    col.with::<Registered<u16>>().register(|_| 0u8);
+    col.with::<Registered<u8>>().register(|_| 0u16);
+    col.with::<(Registered<f32>, Registered<f64>)>()
+        .register(|_| 0u32);
+    let u32_line = line!() - 1;
+
+    let errors = col
+        .build_all_errors()
+        .expect_err("Expected to return errors")
+        .errors;
+    let reported: Vec<_> = errors.iter().map(|e| (e.service, &e.error)).collect();
+    assert_eq!(4, reported.len());
+    assert!(matches!(
+        reported[0],
+        ("u32", BuildError::MissingDependency(_))
+    ));
+    assert!(matches!(
+        reported[1],
+        ("u32", BuildError::MissingDependency(_))
+    ));
+    assert!(reported[1].1.to_string().starts_with(&format!(
+        "Missing dependency ioc_rs::Registered<f64> of u32 registered at {}:{}:",
+        file!(),
+        u32_line
+    )));
+    let names = |error: &BuildError| match error {
+        BuildError::CyclicDependency(cycle) => cycle.iter().map(|(name, _)| *name).collect(),
+        _ => Vec::new(),
+    };
+    assert_eq!(
+        ("i16", vec!["i8", "i16", "i8"]),
+        (reported[2].0, names(reported[2].1))
+    );
+    assert_eq!(
+        ("u16", vec!["u8", "u16", "u8"]),
+        (reported[3].0, names(reported[3].1))
+    );
+}
@@ -0,0 +1,32 @@
+use {
+    ioc_rs::{set_error_handler, LeakPolicy, ServiceCollection},
+    std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn error_handler_is_used_by_providers_without_leak_policy() {
+    set_error_handler(|report| {
+        HANDLED.fetch_add(report.services.len(), Ordering::SeqCst);
+    });
+
+    let mut collection = ServiceCollection::new();
+    collection.register_shared(|| Arc::new(1i32));
+    let provider = collection.build().unwrap();
+    let _leaking = provider.get::<Arc<i32>>();
+    drop(provider);
+
+    let mut collection = ServiceCollection::new();
+    collection.register_shared(|| Arc::new(1i32));
+    collection.set_leak_policy(LeakPolicy::Ignore);
+    let provider = collection.build().unwrap();
+    let _leaking = provider.get::<Arc<i32>>();
+    drop(provider);
+
+    let expected = if cfg!(debug_assertions) { 1 } else { 0 };
+    assert_eq!(expected, HANDLED.load(Ordering::SeqCst));
+}